@@ -68,7 +68,7 @@ export class GameEl extends LitElement {
           .pieces="${new Map(this.board.pieces())}"
           .legal_moves="${this.player_moves()}"
           .handle_move="${(move: Move) => {
-            this.board.make_move(move);
+            this.board.play(move.from, move.to, move.promotion());
             this.handle_game_state_change();
             this.requestUpdate();
 
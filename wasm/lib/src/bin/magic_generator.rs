@@ -1,92 +1,253 @@
 use lib::{
     bitboard::BitBoard,
-    movegen::{
-        magic::occupancy,
-        precalc::{bishop_attack, rook_attack},
-    },
+    movegen::precalc::{bishop_attack, bishop_attack_blocker, rook_attack, rook_attack_blocker},
     square::Square,
+    zobrist::splitmix64,
 };
-use rand::{thread_rng, Rng};
+use std::fmt::Write;
+
+const USAGE: &str = "usage: magic_generator [--seed N] [--reduce BITS] [--tries N]
+                       [--shift PIECE:SQUARE=SHIFT]... [--out PATH]
+
+Searches rook and bishop magics and writes them, with their shifts, as a
+ready to commit movegen/magic.rs.
+
+  --seed N       seed for the random search (default 0)
+  --reduce BITS  try to index each square with this many bits fewer than its
+                 mask has, falling back to the full count (default 1)
+  --tries N      candidates to try per square at a reduced size (default 1000000)
+  --shift PIECE:SQUARE=SHIFT
+                 target shift for one square instead of --reduce, for
+                 example rook:a1=53, falling back the same way
+  --out PATH     where to write the file (default src/movegen/magic.rs of
+                 this crate, `-` for stdout)";
+
+struct Options {
+    seed: u64,
+    reduce: u32,
+    tries: u64,
+    /// `--shift` targets, bishops first, indexed by square.
+    shifts: [[Option<u32>; 64]; 2],
+    out: String,
+}
+
+/// splitmix64, the generator behind the Zobrist keys. Unlike `StdRng` its
+/// output is fixed, so a seed gives the same file whatever `rand` version
+/// the lockfile picks.
+struct SplitMix(u64);
+
+impl SplitMix {
+    fn next(&mut self) -> u64 {
+        let (value, state) = splitmix64(self.0);
+        self.0 = state;
+        value
+    }
+}
 
 fn main() {
-    let h = std::thread::spawn(|| {
-        let mut bishop_magics = [None; 64];
-        for square in 0..64 {
-            loop {
-                let magic: u64 = random_magic();
-                let attack_mask = bishop_attack(square);
-                if is_magic(magic, attack_mask) {
-                    println!(
-                        "magic number found for bishop on square {:?}: {}",
-                        Square::from(square),
-                        magic
-                    );
-                    bishop_magics[square as usize] = Some(magic);
-                    break;
-                }
-            }
+    let options = match parse_args() {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("{}\n\n{}", err, USAGE);
+            std::process::exit(2);
         }
-        bishop_magics
-    });
+    };
 
-    let mut rook_magics = [None; 64];
-    for square in 0..64 {
-        loop {
-            let magic: u64 = random_magic();
-            let attack_mask = rook_attack(square);
-            if is_magic(magic, attack_mask) {
-                println!(
-                    "magic number found for rook on square {:?}: {:x}",
-                    Square::from(square),
-                    magic
-                );
-                rook_magics[square as usize] = Some(magic);
-                break;
+    let mut rng = SplitMix(options.seed);
+    let rook = search(&mut rng, &options, true);
+    let bishop = search(&mut rng, &options, false);
+
+    let source = render(&options, &rook, &bishop);
+    if options.out == "-" {
+        print!("{}", source);
+    } else if let Err(err) = std::fs::write(&options.out, source) {
+        eprintln!("could not write {}: {}", options.out, err);
+        std::process::exit(1);
+    } else {
+        eprintln!("wrote {}", options.out);
+    }
+}
+
+fn parse_args() -> Result<Options, String> {
+    let mut options = Options {
+        seed: 0,
+        reduce: 1,
+        tries: 1_000_000,
+        shifts: [[None; 64]; 2],
+        out: concat!(env!("CARGO_MANIFEST_DIR"), "/src/movegen/magic.rs").to_string(),
+    };
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("missing value for {}", arg));
+        match arg.as_str() {
+            "--seed" => options.seed = parse(&value()?)?,
+            "--reduce" => options.reduce = parse(&value()?)?,
+            "--tries" => options.tries = parse(&value()?)?,
+            "--shift" => {
+                let (rook, square, shift) = parse_shift(&value()?)?;
+                options.shifts[rook as usize][square] = Some(shift);
             }
+            "--out" => options.out = value()?,
+            "-h" | "--help" => return Err("magic_generator".to_string()),
+            _ => return Err(format!("unknown argument {}", arg)),
         }
     }
+    Ok(options)
+}
 
-    println!("magic numbers found!");
-    println!(
-        "rook magics: {:#x?}",
-        rook_magics.iter().map(|x| x.unwrap()).collect::<Vec<_>>()
-    );
-    println!(
-        "bishop magics: {:#x?}",
-        h.join()
-            .unwrap()
-            .iter()
-            .map(|x| x.unwrap())
-            .collect::<Vec<_>>()
-    );
+fn parse<T: std::str::FromStr>(s: &str) -> Result<T, String> {
+    s.parse().map_err(|_| format!("invalid number {}", s))
 }
 
-fn is_magic(magic_num: u64, attack_mask: BitBoard) -> bool {
-    let attack_bits = attack_mask.0.count_ones();
-    let max_idx = 1 << attack_bits;
-    let mut magic_idxs = [0; 4096];
+/// Parses `rook:a1=53` into whether it is a rook, the square and the shift.
+fn parse_shift(s: &str) -> Result<(bool, usize, u32), String> {
+    let invalid = || format!("invalid shift {}, expected PIECE:SQUARE=SHIFT", s);
+    let (piece, rest) = s.split_once(':').ok_or_else(invalid)?;
+    let (square, shift) = rest.split_once('=').ok_or_else(invalid)?;
+    let rook = match piece {
+        "rook" => true,
+        "bishop" => false,
+        _ => return Err(format!("unknown piece {}", piece)),
+    };
+    let square = match square.as_bytes() {
+        &[file @ b'a'..=b'h', rank @ b'1'..=b'8'] => ((rank - b'1') * 8 + file - b'a') as usize,
+        _ => return Err(format!("invalid square {}", square)),
+    };
+    match parse(shift)? {
+        shift @ 1..=63 => Ok((rook, square, shift)),
+        shift => Err(format!("shift {} out of range", shift)),
+    }
+}
 
-    for idx in 0..max_idx {
-        let occupancy = occupancy(idx, attack_mask);
-        let magic_idx = occupancy.0.wrapping_mul(magic_num) >> (64 - attack_bits);
-        if magic_idxs[magic_idx as usize] != 0 {
-            break;
-        }
-        magic_idxs[magic_idx as usize] = 1;
+/// Finds a magic and shift for every square, smallest table first.
+fn search(rng: &mut SplitMix, options: &Options, rook: bool) -> [(u64, u32); 64] {
+    let mut res = [(0, 0); 64];
+    for (square, entry) in res.iter_mut().enumerate() {
+        let target = options.shifts[rook as usize][square];
+        let square = square as u64;
+        let (mask, attack): (BitBoard, fn(u64, BitBoard) -> BitBoard) = if rook {
+            (rook_attack(square), rook_attack_blocker)
+        } else {
+            (bishop_attack(square), bishop_attack_blocker)
+        };
+        let table = Table::new(square, mask, attack);
+        let full = mask.0.count_ones();
+
+        let reduced = match target {
+            Some(shift) => (64 - shift).min(full),
+            None => full.saturating_sub(options.reduce),
+        };
+        *entry = (reduced..full)
+            .find_map(|bits| table.find(rng, bits, Some(options.tries)))
+            .or_else(|| table.find(rng, full, None))
+            .unwrap();
+
+        eprintln!(
+            "{} on {}: {:#x}, {} bits of {}",
+            if rook { "rook" } else { "bishop" },
+            Square::from(square),
+            entry.0,
+            64 - entry.1,
+            full
+        );
     }
+    res
+}
 
-    magic_idxs.iter().take(max_idx as usize).all(|x| *x != 0)
+/// Every blocker subset of a square's mask with the attack it produces.
+struct Table {
+    occupancies: Vec<u64>,
+    attacks: Vec<BitBoard>,
 }
 
-fn random_magic() -> u64 {
-    let rand_num = || {
-        let n1 = thread_rng().gen::<u64>() & 0xFFFF;
-        let n2 = thread_rng().gen::<u64>() & 0xFFFF;
-        let n3 = thread_rng().gen::<u64>() & 0xFFFF;
-        let n4 = thread_rng().gen::<u64>() & 0xFFFF;
+impl Table {
+    fn new(square: u64, mask: BitBoard, attack: fn(u64, BitBoard) -> BitBoard) -> Self {
+        let mut occupancies = Vec::new();
+        let mut attacks = Vec::new();
+        let mut occ = 0u64;
+        loop {
+            occupancies.push(occ);
+            attacks.push(attack(square, BitBoard(occ)));
+            occ = occ.wrapping_sub(mask.0) & mask.0;
+            if occ == 0 {
+                break;
+            }
+        }
+        Self {
+            occupancies,
+            attacks,
+        }
+    }
 
-        n1 | (n2 << 16) | (n3 << 32) | (n4 << 48)
-    };
+    /// Tries random sparse candidates until one maps every occupancy into
+    /// `bits` index bits without two different attacks sharing a slot.
+    /// Returns the magic and its shift.
+    fn find(&self, rng: &mut SplitMix, bits: u32, tries: Option<u64>) -> Option<(u64, u32)> {
+        let shift = 64 - bits;
+        let mut slots = vec![(0, BitBoard(0)); 1 << bits];
+        let mut tried = 0;
+        while tries.is_none_or(|tries| tried < tries) {
+            tried += 1;
+            let magic = rng.next() & rng.next() & rng.next();
+            if self.verify(magic, shift, tried, &mut slots) {
+                return Some((magic, shift));
+            }
+        }
+        None
+    }
 
-    rand_num() & rand_num() & rand_num()
+    /// Slots are tagged with the attempt that filled them, so they never
+    /// have to be cleared between candidates.
+    fn verify(&self, magic: u64, shift: u32, attempt: u64, slots: &mut [(u64, BitBoard)]) -> bool {
+        for (&occ, &attack) in self.occupancies.iter().zip(&self.attacks) {
+            let slot = &mut slots[(occ.wrapping_mul(magic) >> shift) as usize];
+            if slot.0 != attempt {
+                *slot = (attempt, attack);
+            } else if slot.1 != attack {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+fn render(options: &Options, rook: &[(u64, u32); 64], bishop: &[(u64, u32); 64]) -> String {
+    let mut s = String::new();
+    let mut command = format!(
+        "cargo run --release --bin magic_generator -- --seed {} --reduce {} --tries {}",
+        options.seed, options.reduce, options.tries
+    );
+    for (rook, shifts) in options.shifts.iter().enumerate().rev() {
+        for (square, shift) in shifts.iter().enumerate() {
+            if let Some(shift) = shift {
+                let piece = if rook == 1 { "rook" } else { "bishop" };
+                let square = Square::from(square as u64);
+                write!(command, " --shift {}:{}={}", piece, square, shift).unwrap();
+            }
+        }
+    }
+    writeln!(s, "// Generated by `{}`.", command).unwrap();
+    writeln!(s, "// Do not edit by hand.").unwrap();
+    for (name, entries, magic) in [
+        ("ROOK_MAGIC", rook, true),
+        ("ROOK_SHIFT", rook, false),
+        ("BISHOP_MAGIC", bishop, true),
+        ("BISHOP_SHIFT", bishop, false),
+    ] {
+        if magic {
+            writeln!(s, "\npub const {}: [u64; 64] = [", name).unwrap();
+            for &(m, _) in entries {
+                writeln!(s, "    {:#018x},", m).unwrap();
+            }
+        } else {
+            writeln!(s, "\n#[rustfmt::skip]\npub const {}: [u32; 64] = [", name).unwrap();
+            for rank in entries.chunks(8) {
+                let shifts = rank.iter().map(|(_, shift)| format!("{},", shift));
+                writeln!(s, "    {}", shifts.collect::<Vec<_>>().join(" ")).unwrap();
+            }
+        }
+        writeln!(s, "];").unwrap();
+    }
+    s
 }
@@ -1,48 +1,278 @@
-#![allow(unused)]
-
 use lib::{
     board::{Board, DEFAULT_FEN},
-    movegen::{generate_moves, legal_moves, precalc::Precalc},
+    movegen::Move,
+    perft::{divide_parallel, perft_parallel, perft_stats, EpdEntry, PerftCache, ZeroDepthDivide},
 };
+use std::{process::ExitCode, time::Instant};
+
+const USAGE: &str = "usage: perft [OPTIONS] [--divide | --stats] [--expect NODES] DEPTH [FEN]
+       perft [OPTIONS] [--max-depth DEPTH] --epd FILE
+
+Counts the leaf nodes of the move tree of FEN (the start position if not
+given) or of every position in an EPD suite, where each line is a FEN
+followed by expected counts like `;D1 20 ;D2 400`.
+
+  --divide          print the node count under each root move
+  --stats           break the count down into captures, checks and so on
+                    at every depth up to DEPTH
+  --expect NODES    fail unless the total matches
+  --epd FILE        run every position and depth in FILE
+  --max-depth DEPTH skip EPD entries deeper than DEPTH
+
+Options:
+  --threads N       split the root moves over N threads (default: all cores)
+  --hash MB         cache subtree counts in a table of MB megabytes
+
+Exits with status 1 if any count does not match.";
+
+/// How the counts are computed, shared by every mode but `--stats`.
+struct Search {
+    threads: usize,
+    cache: Option<PerftCache>,
+}
+
+impl Search {
+    fn divide(&self, board: &Board, depth: u32) -> Vec<(Move, u64)> {
+        divide_parallel(board, depth, self.threads, self.cache.as_ref())
+    }
+
+    fn perft(&self, board: &Board, depth: u32) -> u64 {
+        perft_parallel(board, depth, self.threads, self.cache.as_ref())
+    }
+}
+
+enum Mode {
+    Single {
+        fen: String,
+        depth: u32,
+        divide: bool,
+        stats: bool,
+        expect: Option<u64>,
+    },
+    Epd {
+        path: String,
+        max_depth: u32,
+    },
+}
+
+fn main() -> ExitCode {
+    let (mode, search) = match parse_args() {
+        Ok(args) => args,
+        Err(err) => {
+            eprintln!("{}\n\n{}", err, USAGE);
+            return ExitCode::from(2);
+        }
+    };
+
+    let ok = match mode {
+        Mode::Single {
+            fen,
+            depth,
+            divide,
+            stats,
+            expect,
+        } => match Board::try_from_fen_strict(&fen) {
+            Ok(mut board) if stats => run_stats(&mut board, depth, expect),
+            Ok(board) => run_single(&search, &board, depth, divide, expect),
+            Err(err) => {
+                eprintln!("invalid FEN: {}", err);
+                return ExitCode::from(2);
+            }
+        },
+        Mode::Epd { path, max_depth } => {
+            let suite = std::fs::read_to_string(&path)
+                .map_err(|err| err.to_string())
+                .and_then(|s| EpdEntry::parse_suite(&s).map_err(|err| err.to_string()));
+            match suite {
+                Ok(suite) => run_suite(&search, &suite, max_depth),
+                Err(err) => {
+                    eprintln!("could not read {}: {}", path, err);
+                    return ExitCode::from(2);
+                }
+            }
+        }
+    };
+
+    if ok {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+fn parse_args() -> Result<(Mode, Search), String> {
+    let mut threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let mut hash = 0;
+    let mut divide = false;
+    let mut stats = false;
+    let mut expect = None;
+    let mut epd = None;
+    let mut max_depth = u32::MAX;
+    let mut positional = Vec::new();
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("missing value for {}", arg));
+        match arg.as_str() {
+            "--divide" => divide = true,
+            "--stats" => stats = true,
+            "--expect" => expect = Some(parse(&value()?)?),
+            "--epd" => epd = Some(value()?),
+            "--max-depth" => max_depth = parse(&value()?)?,
+            "--threads" => threads = parse(&value()?)?,
+            "--hash" => hash = parse(&value()?)?,
+            "-h" | "--help" => return Err("perft".to_string()),
+            _ if arg.starts_with("--") => return Err(format!("unknown argument {}", arg)),
+            _ => positional.push(arg),
+        }
+    }
+
+    let search = Search {
+        threads,
+        cache: (hash > 0).then(|| PerftCache::new(hash)),
+    };
+
+    if let Some(path) = epd {
+        if !positional.is_empty() {
+            return Err("--epd does not take a depth or FEN".to_string());
+        }
+        return Ok((Mode::Epd { path, max_depth }, search));
+    }
+
+    let depth = parse(positional.first().ok_or("missing depth")?)?;
+    let fen = if positional.len() > 1 {
+        positional[1..].join(" ")
+    } else {
+        DEFAULT_FEN.to_string()
+    };
+    if divide && stats {
+        return Err("--divide and --stats cannot be combined".to_string());
+    }
+    if divide && depth == 0 {
+        return Err(ZeroDepthDivide.to_string());
+    }
+    let mode = Mode::Single {
+        fen,
+        depth,
+        divide,
+        stats,
+        expect,
+    };
+    Ok((mode, search))
+}
+
+fn parse<T: std::str::FromStr>(s: &str) -> Result<T, String> {
+    s.parse().map_err(|_| format!("invalid number {}", s))
+}
+
+fn run_single(
+    search: &Search,
+    board: &Board,
+    depth: u32,
+    show_divide: bool,
+    expect: Option<u64>,
+) -> bool {
+    let start = Instant::now();
+    let nodes = if show_divide {
+        let moves = search.divide(board, depth);
+        for (m, count) in &moves {
+            println!("{} {}", m, count);
+        }
+        println!();
+        moves.iter().map(|(_, count)| count).sum()
+    } else {
+        search.perft(board, depth)
+    };
+    let elapsed = start.elapsed().as_secs_f64();
+
+    println!(
+        "depth {} nodes {} time {:.3}s nps {:.0}",
+        depth,
+        nodes,
+        elapsed,
+        nodes as f64 / elapsed
+    );
+    match expect {
+        Some(expected) if expected != nodes => {
+            println!("MISMATCH: expected {}", expected);
+            false
+        }
+        _ => true,
+    }
+}
+
+fn run_stats(board: &mut Board, depth: u32, expect: Option<u64>) -> bool {
+    println!(
+        "{:>5} {:>12} {:>10} {:>8} {:>8} {:>10} {:>9} {:>9} {:>7} {:>9}",
+        "depth",
+        "nodes",
+        "captures",
+        "e.p.",
+        "castles",
+        "promotions",
+        "checks",
+        "disc.",
+        "double",
+        "mates"
+    );
+    let mut nodes = 0;
+    for d in 1..=depth {
+        let stats = perft_stats(board, d);
+        println!(
+            "{:>5} {:>12} {:>10} {:>8} {:>8} {:>10} {:>9} {:>9} {:>7} {:>9}",
+            d,
+            stats.nodes,
+            stats.captures,
+            stats.en_passants,
+            stats.castles,
+            stats.promotions,
+            stats.checks,
+            stats.discovered_checks,
+            stats.double_checks,
+            stats.checkmates
+        );
+        nodes = stats.nodes;
+    }
+
+    match expect {
+        Some(expected) if expected != nodes => {
+            println!("MISMATCH: expected {}", expected);
+            false
+        }
+        _ => true,
+    }
+}
+
+fn run_suite(search: &Search, suite: &[EpdEntry], max_depth: u32) -> bool {
+    let start = Instant::now();
+    let mut total = 0;
+    let mut failures = 0;
 
-fn main() {
-    // let args = std::env::args().collect::<Vec<_>>();
-    // let depth: u32 = args.get(1).map(|x| x.parse().unwrap()).unwrap();
-    // let mut board = Board::from_fen(args.get(2).unwrap());
-    // let mut board =
-    //     Board::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - ");
-    // let mut board =
-    //     Board::from_fen("r3k2r/p1pNqpb1/bn2pnp1/3P4/1p2P3/2N2Q1p/PPPBBPPP/R3K2R b KQkq - 0 1");
-    // for i in 1..=1 {
-    //     println!("Depth {}", i);
-
-    let start = std::time::Instant::now();
-    let mut board =
-        Board::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - ");
-    let res = perft(5, 5, &mut board);
-    println!("Total: {} in {:?}", res, start.elapsed());
-    println!();
-    // }
-}
-
-fn perft(start_depth: u32, depth: u32, board: &mut Board) -> u128 {
-    if depth == 0 {
-        return 1;
-    }
-
-    let moves = generate_moves(board);
-    let mut count = 0;
-
-    for m in moves {
-        let mut b = board.clone();
-        if b.make_move(m) {
-            let x = perft(start_depth, depth - 1, &mut b);
-            if depth == start_depth {
-                println!("{} {}", m, x);
+    for entry in suite {
+        println!("{}", entry.fen);
+        let board = Board::from_fen(&entry.fen);
+        for &(depth, expected) in entry.counts.iter().filter(|(d, _)| *d <= max_depth) {
+            let nodes = search.perft(&board, depth);
+            total += nodes;
+            if nodes == expected {
+                println!("  depth {} nodes {} ok", depth, nodes);
+            } else {
+                println!(
+                    "  depth {} nodes {} MISMATCH: expected {}",
+                    depth, nodes, expected
+                );
+                failures += 1;
             }
-            count += x;
         }
     }
 
-    count
+    let elapsed = start.elapsed().as_secs_f64();
+    println!(
+        "{} failures, {} nodes in {:.3}s, nps {:.0}",
+        failures,
+        total,
+        elapsed,
+        total as f64 / elapsed
+    );
+    failures == 0
 }
@@ -1,9 +1,4 @@
-use lib::{
-    board::{Board, DEFAULT_FEN},
-    movegen::{precalc::rook_attack, *},
-    piece::Color,
-    square::Square,
-};
+use lib::{board::Board, movegen::*};
 
 #[allow(dead_code)]
 const TRICKY_FEN: &str = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R b KQkq - 0 1 ";
@@ -14,5 +9,5 @@ fn main() {
     let legal_moves = generate_moves(&board);
     let m = legal_moves[4];
     println!("{}", m);
-    println!("{}", board.make_move(m));
+    println!("{:?}", board.make_move(m));
 }
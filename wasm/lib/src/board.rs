@@ -1,15 +1,26 @@
 #![allow(dead_code)]
 
 use crate::{
-    bitboard::BitBoard,
+    bitboard::*,
+    bitboardindex::BitBoardIdx,
     console_log,
-    movegen::{generate_moves, legal_moves, square_attacked, Move, SpecialMove},
+    movegen::{
+        en_passant_legal, generate_moves, pawn_attacks, precalc::PRECALC, square_attacked, Move,
+        SpecialMove,
+    },
     piece::*,
     square::Square,
+    zobrist::ZOBRIST,
 };
+use std::ops::{Index, IndexMut};
 use wasm_bindgen::prelude::*;
 pub const DEFAULT_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
 
+/// Halfmove clock values at which a draw can be claimed and at which the game
+/// is drawn automatically.
+pub const FIFTY_MOVE_RULE: u32 = 100;
+pub const SEVENTY_FIVE_MOVE_RULE: u32 = 150;
+
 #[derive(Debug, Clone, Default)]
 #[wasm_bindgen]
 pub struct Board {
@@ -33,8 +44,16 @@ pub struct Board {
     pub side_to_move: Color,
     pub(crate) en_passant: Option<Square>,
     pub(crate) can_castle: u8,
+    pub(crate) hash: u64,
+    pub(crate) mailbox: Mailbox,
+    pub(crate) history: Vec<u64>,
+    pub halfmove_clock: u32,
+    pub fullmove_number: u32,
 
     pub game_state: GameState,
+    pub draw_reason: Option<DrawReason>,
+    pub claimable_draw: Option<DrawReason>,
+    pub(crate) draw_claimed: bool,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
@@ -47,6 +66,102 @@ pub enum GameState {
     Draw,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[wasm_bindgen]
+pub enum DrawReason {
+    FiftyMoveRule,
+    SeventyFiveMoveRule,
+    ThreefoldRepetition,
+    FivefoldRepetition,
+    InsufficientMaterial,
+    DeadPosition,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Mailbox([Option<Piece>; 64]);
+
+impl Default for Mailbox {
+    fn default() -> Self {
+        Self([None; 64])
+    }
+}
+
+impl<I: BitBoardIdx> Index<I> for Mailbox {
+    type Output = Option<Piece>;
+
+    fn index(&self, idx: I) -> &Self::Output {
+        &self.0[idx.idx_usize()]
+    }
+}
+
+impl<I: BitBoardIdx> IndexMut<I> for Mailbox {
+    fn index_mut(&mut self, idx: I) -> &mut Self::Output {
+        &mut self.0[idx.idx_usize()]
+    }
+}
+
+/// Everything `make_move` overwrites that cannot be recomputed from the move
+/// itself. `captured` also holds the pawn taken by an en passant capture.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Undo {
+    pub captured: Option<Piece>,
+    pub can_castle: u8,
+    pub en_passant: Option<Square>,
+    pub halfmove_clock: u32,
+    pub hash: u64,
+    pub draw_claimed: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MoveError {
+    WrongSide(Color),
+    PieceMismatch {
+        square: Square,
+        expected: Piece,
+        found: Option<Piece>,
+    },
+    OwnPieceCaptured(Square),
+    KingCaptured(Square),
+    CaptureMismatch(Square),
+    MissingPromotion(Square),
+    InvalidSpecial(SpecialMove),
+    IllegalMove(Move),
+    DrawClaimed,
+    NoLegalMove {
+        from: Square,
+        to: Square,
+    },
+}
+
+impl std::fmt::Display for MoveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::WrongSide(color) => write!(f, "it is not {:?}'s turn", color),
+            Self::PieceMismatch {
+                square,
+                expected,
+                found,
+            } => write!(
+                f,
+                "expected {:?} on {}, found {:?}",
+                expected, square, found
+            ),
+            Self::OwnPieceCaptured(sq) => write!(f, "cannot capture own piece on {}", sq),
+            Self::KingCaptured(sq) => write!(f, "cannot capture the king on {}", sq),
+            Self::CaptureMismatch(sq) => {
+                write!(f, "capture flag does not match the contents of {}", sq)
+            }
+            Self::MissingPromotion(sq) => write!(f, "pawn move to {} must promote", sq),
+            Self::InvalidSpecial(special) => write!(f, "invalid {:?} move", special),
+            Self::IllegalMove(m) => write!(f, "{} is not a legal move", m),
+            Self::DrawClaimed => write!(f, "the game has been drawn by claim"),
+            Self::NoLegalMove { from, to } => write!(f, "no legal move from {} to {}", from, to),
+        }
+    }
+}
+
+impl std::error::Error for MoveError {}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub(crate) enum Castle {
     WhiteKing = 0b0001,
@@ -57,18 +172,96 @@ pub(crate) enum Castle {
 
 #[wasm_bindgen]
 impl Board {
-    pub fn make_move(&mut self, m: Move) -> bool {
+    #[wasm_bindgen(js_name = make_move)]
+    pub fn make_move_js(&mut self, m: Move) -> Result<(), JsError> {
+        self.make_move(m)?;
+        Ok(())
+    }
+
+    #[wasm_bindgen(js_name = play)]
+    pub fn play_js(
+        &mut self,
+        from: Square,
+        to: Square,
+        promotion: Option<PieceKind>,
+    ) -> Result<Move, JsError> {
+        Ok(self.play(from, to, promotion)?)
+    }
+
+    pub fn is_legal(&self, m: &Move) -> bool {
+        generate_moves(self).contains(m)
+    }
+}
+
+impl Board {
+    /// Looks up the legal move from `from` to `to` and plays it. `promotion`
+    /// is required for pawn moves to the last rank and ignored otherwise.
+    pub fn play(
+        &mut self,
+        from: Square,
+        to: Square,
+        promotion: Option<PieceKind>,
+    ) -> Result<Move, MoveError> {
+        if self.draw_claimed {
+            return Err(MoveError::DrawClaimed);
+        }
+        let moves = generate_moves(self);
+        let mut candidates = moves
+            .iter()
+            .filter(|m| m.from == from && m.to == to)
+            .peekable();
+        if candidates.peek().is_none() {
+            return Err(MoveError::NoLegalMove { from, to });
+        }
+        let m = *candidates
+            .find(|m| m.promotion().is_none() || m.promotion() == promotion)
+            .ok_or(MoveError::MissingPromotion(to))?;
+        self.make_move_unchecked(m);
+        Ok(m)
+    }
+
+    /// Plays `m` and returns the record needed to take it back. Moves the
+    /// generator does not produce for this position are rejected and leave
+    /// the board untouched.
+    pub fn make_move(&mut self, m: Move) -> Result<Undo, MoveError> {
+        if self.draw_claimed {
+            return Err(MoveError::DrawClaimed);
+        }
+        self.check_move(&m)?;
+        if !self.is_legal(&m) {
+            return Err(MoveError::IllegalMove(m));
+        }
+        Ok(self.make_move_unchecked(m))
+    }
+
+    /// Plays `m` without checking it against the position or for leaving the
+    /// king in check. `m` must come from the move generator for this position.
+    pub fn make_move_unchecked(&mut self, m: Move) -> Undo {
+        let mut undo = Undo {
+            captured: None,
+            can_castle: self.can_castle,
+            en_passant: self.en_passant,
+            halfmove_clock: self.halfmove_clock,
+            hash: self.hash,
+            draw_claimed: self.draw_claimed,
+        };
+        self.hash ^= self.en_passant_hash();
+        self.hash ^= ZOBRIST.castling[self.can_castle as usize];
+
+        let captured = self.mailbox[m.to];
         let bb = self.board(m.piece);
         bb.clear(m.from);
         bb.set(m.to);
+        self.mailbox[m.from] = None;
+        self.mailbox[m.to] = Some(m.piece);
+        self.hash ^= ZOBRIST.piece(m.piece, m.from) ^ ZOBRIST.piece(m.piece, m.to);
         self.en_passant = None;
 
         if m.capture {
-            for b in self.boards_color(self.side_to_move.opposite()) {
-                if b.get(m.to) {
-                    b.clear(m.to);
-                    break;
-                }
+            if let Some(p) = captured {
+                self.board(p).clear(m.to);
+                self.hash ^= ZOBRIST.piece(p, m.to);
+                undo.captured = Some(p);
             }
 
             if self.side_to_move == Color::White {
@@ -94,11 +287,16 @@ impl Board {
                 })
             }
             Some(SpecialMove::EnPassant) => {
-                if self.side_to_move == Color::White {
-                    self.b_pawn.clear(m.to as u64 - 8)
+                let (pawn, sq) = if self.side_to_move == Color::White {
+                    self.b_pawn.clear(m.to as u64 - 8);
+                    (Piece::new(PieceKind::Pawn, Color::Black), m.to as u64 - 8)
                 } else {
-                    self.w_pawn.clear(m.to as u64 + 8)
-                }
+                    self.w_pawn.clear(m.to as u64 + 8);
+                    (Piece::new(PieceKind::Pawn, Color::White), m.to as u64 + 8)
+                };
+                self.mailbox[sq] = None;
+                self.hash ^= ZOBRIST.piece(pawn, sq);
+                undo.captured = Some(pawn);
             }
             Some(SpecialMove::Promotion(p)) => {
                 let b = self.boards_color(self.side_to_move);
@@ -110,26 +308,25 @@ impl Board {
                     PieceKind::Queen => b[4].set(m.to),
                     _ => unreachable!(),
                 }
+                let promoted = Piece::new(p, self.side_to_move);
+                self.mailbox[m.to] = Some(promoted);
+                self.hash ^= ZOBRIST.piece(m.piece, m.to) ^ ZOBRIST.piece(promoted, m.to);
+            }
+            Some(SpecialMove::Castle) => {
+                let (rook, from, to) = match m.to {
+                    Square::G1 => (&mut self.w_rook, Square::H1, Square::F1),
+                    Square::C1 => (&mut self.w_rook, Square::A1, Square::D1),
+                    Square::G8 => (&mut self.b_rook, Square::H8, Square::F8),
+                    Square::C8 => (&mut self.b_rook, Square::A8, Square::D8),
+                    _ => unreachable!(),
+                };
+                rook.clear(from);
+                rook.set(to);
+                let rook = Piece::new(PieceKind::Rook, self.side_to_move);
+                self.mailbox[from] = None;
+                self.mailbox[to] = Some(rook);
+                self.hash ^= ZOBRIST.piece(rook, from) ^ ZOBRIST.piece(rook, to);
             }
-            Some(SpecialMove::Castle) => match m.to {
-                Square::G1 => {
-                    self.w_rook.clear(Square::H1);
-                    self.w_rook.set(Square::F1);
-                }
-                Square::C1 => {
-                    self.w_rook.clear(Square::A1);
-                    self.w_rook.set(Square::D1);
-                }
-                Square::G8 => {
-                    self.b_rook.clear(Square::H8);
-                    self.b_rook.set(Square::F8);
-                }
-                Square::C8 => {
-                    self.b_rook.clear(Square::A8);
-                    self.b_rook.set(Square::D8);
-                }
-                _ => unreachable!(),
-            },
             _ => (),
         }
 
@@ -165,15 +362,28 @@ impl Board {
         }
         self.update_occ();
 
-        let king = self.boards_color(self.side_to_move)[5].0.trailing_zeros() as u64;
-        if square_attacked(self, king, self.side_to_move.opposite()) {
-            return false;
+        if m.capture || m.piece.kind == PieceKind::Pawn {
+            self.halfmove_clock = 0;
+        } else {
+            self.halfmove_clock += 1;
+        }
+        if self.side_to_move == Color::Black {
+            self.fullmove_number += 1;
         }
-
         self.side_to_move = self.side_to_move.opposite();
-        true
+
+        self.history.push(undo.hash);
+        self.hash ^= ZOBRIST.castling[self.can_castle as usize];
+        self.hash ^= ZOBRIST.side_to_move;
+        self.hash ^= self.en_passant_hash();
+
+        debug_assert!(self.mailbox_matches_bitboards());
+        undo
     }
+}
 
+#[wasm_bindgen]
+impl Board {
     fn board(&mut self, piece: Piece) -> &mut BitBoard {
         match piece.color {
             Color::White => match piece.kind {
@@ -230,144 +440,271 @@ impl Board {
     pub fn print(&self) {
         console_log!("{}", self);
     }
+
+    pub fn hash(&self) -> u64 {
+        self.hash
+    }
 }
 
-#[wasm_bindgen]
 impl Board {
-    pub fn start_pos() -> Board {
-        Self::from_fen(DEFAULT_FEN)
-    }
+    /// Checks that `m` describes what is actually on the board, so that
+    /// playing it keeps the bitboards consistent. It does not check that the
+    /// piece can reach the target square.
+    pub fn check_move(&self, m: &Move) -> Result<(), MoveError> {
+        if m.piece.color != self.side_to_move {
+            return Err(MoveError::WrongSide(m.piece.color));
+        }
+        let found = self.piece(m.from as u64);
+        if found != Some(m.piece) {
+            return Err(MoveError::PieceMismatch {
+                square: m.from,
+                expected: m.piece,
+                found,
+            });
+        }
 
-    pub fn from_fen(fen: &str) -> Board {
-        // TODO: move to other function
-        console_error_panic_hook::set_once();
-
-        let mut s = Self::default();
-
-        let mut parts = fen.split_whitespace();
-
-        // Piece placement
-        let board = parts.next().unwrap();
-        for (i, l) in board.split('/').enumerate() {
-            let i = 7 - i;
-            let mut j = 0;
-            for c in l.chars() {
-                let idx = (i * 8 + j) as u64;
-                match c {
-                    '1'..='8' => j += c.to_digit(10).unwrap() as usize - 1,
-                    'p' => s.b_pawn.set(idx),
-                    'n' => s.b_knight.set(idx),
-                    'b' => s.b_bishop.set(idx),
-                    'r' => s.b_rook.set(idx),
-                    'q' => s.b_queen.set(idx),
-                    'k' => s.b_king.set(idx),
-                    'P' => s.w_pawn.set(idx),
-                    'N' => s.w_knight.set(idx),
-                    'B' => s.w_bishop.set(idx),
-                    'R' => s.w_rook.set(idx),
-                    'Q' => s.w_queen.set(idx),
-                    'K' => s.w_king.set(idx),
-                    _ => panic!("Invalid FEN"),
-                }
-                j += 1;
+        let target = self.piece(m.to as u64);
+        if let Some(p) = target {
+            if p.color == m.piece.color {
+                return Err(MoveError::OwnPieceCaptured(m.to));
             }
+            if p.kind == PieceKind::King {
+                return Err(MoveError::KingCaptured(m.to));
+            }
+        }
+        let en_passant = matches!(m.special, Some(SpecialMove::EnPassant));
+        if m.capture != (target.is_some() || en_passant) {
+            return Err(MoveError::CaptureMismatch(m.to));
         }
 
-        // Side to move
-        match parts.next().unwrap() {
-            "w" => s.side_to_move = Color::White,
-            "b" => s.side_to_move = Color::Black,
-            _ => panic!("Invalid FEN"),
+        let pawn = m.piece.kind == PieceKind::Pawn;
+        let (start_rank, last_rank, forward) = match self.side_to_move {
+            Color::White => (1, 7, 8),
+            Color::Black => (6, 0, -8),
         };
+        let valid = match m.special {
+            None => !pawn || m.to.rank() != last_rank,
+            Some(SpecialMove::Promotion(kind)) => {
+                let piece = !matches!(kind, PieceKind::Pawn | PieceKind::King);
+                pawn && piece && m.to.rank() == last_rank
+            }
+            Some(SpecialMove::DoublePawnPush) => {
+                let to = m.from as i64 + 2 * forward;
+                pawn && m.from.rank() == start_rank && m.to as i64 == to
+            }
+            Some(SpecialMove::EnPassant) => {
+                let behind = (m.to as i64 - forward) as u64;
+                let them = Piece::new(PieceKind::Pawn, self.side_to_move.opposite());
+                pawn && self.en_passant == Some(m.to) && self.piece(behind) == Some(them)
+            }
+            Some(SpecialMove::Castle) => {
+                let (castle, rook_sq) = match (m.from, m.to) {
+                    (Square::E1, Square::G1) => (Castle::WhiteKing, Square::H1),
+                    (Square::E1, Square::C1) => (Castle::WhiteQueen, Square::A1),
+                    (Square::E8, Square::G8) => (Castle::BlackKing, Square::H8),
+                    (Square::E8, Square::C8) => (Castle::BlackQueen, Square::A8),
+                    _ => return Err(MoveError::InvalidSpecial(SpecialMove::Castle)),
+                };
+                let rook = Piece::new(PieceKind::Rook, m.piece.color);
+                m.piece.kind == PieceKind::King
+                    && self.can_castle & castle as u8 != 0
+                    && self.piece(rook_sq as u64) == Some(rook)
+            }
+        };
+        match (valid, m.special) {
+            (true, _) => Ok(()),
+            (false, Some(special)) => Err(MoveError::InvalidSpecial(special)),
+            (false, None) => Err(MoveError::MissingPromotion(m.to)),
+        }
+    }
 
-        // Castling availability
-        let castling = parts.next().unwrap();
-        for c in castling.chars() {
-            match c {
-                'K' => s.can_castle |= Castle::WhiteKing as u8,
-                'Q' => s.can_castle |= Castle::WhiteQueen as u8,
-                'k' => s.can_castle |= Castle::BlackKing as u8,
-                'q' => s.can_castle |= Castle::BlackQueen as u8,
-                '-' => (),
-                _ => panic!("Invalid FEN"),
+    /// Takes back `m`, which must be the last move played with the `undo`
+    /// record it returned.
+    pub fn unmake_move(&mut self, m: Move, undo: Undo) {
+        self.side_to_move = self.side_to_move.opposite();
+        if self.side_to_move == Color::Black {
+            self.fullmove_number -= 1;
+        }
+        self.history.pop();
+
+        let placed = match m.special {
+            Some(SpecialMove::Promotion(p)) => Piece::new(p, self.side_to_move),
+            _ => m.piece,
+        };
+        self.board(placed).clear(m.to);
+        self.board(m.piece).set(m.from);
+        self.mailbox[m.to] = None;
+        self.mailbox[m.from] = Some(m.piece);
+
+        match (m.special, undo.captured) {
+            (Some(SpecialMove::EnPassant), Some(pawn)) => {
+                let sq = match self.side_to_move {
+                    Color::White => m.to as u64 - 8,
+                    Color::Black => m.to as u64 + 8,
+                };
+                self.board(pawn).set(sq);
+                self.mailbox[sq] = Some(pawn);
+            }
+            (_, Some(captured)) => {
+                self.board(captured).set(m.to);
+                self.mailbox[m.to] = Some(captured);
             }
+            _ => (),
         }
 
-        // En passant target square
-        let en_passant = parts.next().unwrap();
-        if en_passant != "-" {
-            let file = en_passant.chars().nth(0).unwrap() as u8 - b'a';
-            let rank = en_passant.chars().nth(1).unwrap() as u8 - b'1';
-            s.en_passant = Some(Square::from((rank * 8 + file) as u64));
+        if let Some(SpecialMove::Castle) = m.special {
+            let (rook, from, to) = match m.to {
+                Square::G1 => (&mut self.w_rook, Square::H1, Square::F1),
+                Square::C1 => (&mut self.w_rook, Square::A1, Square::D1),
+                Square::G8 => (&mut self.b_rook, Square::H8, Square::F8),
+                Square::C8 => (&mut self.b_rook, Square::A8, Square::D8),
+                _ => unreachable!(),
+            };
+            rook.clear(to);
+            rook.set(from);
+            self.mailbox[from] = self.mailbox[to].take();
         }
 
-        // Halfmove clock
-        // Fullmove number
+        self.can_castle = undo.can_castle;
+        self.en_passant = undo.en_passant;
+        self.halfmove_clock = undo.halfmove_clock;
+        self.hash = undo.hash;
+        self.draw_claimed = undo.draw_claimed;
+        self.update_occ();
 
-        s.update_occ();
-        s
+        debug_assert!(self.mailbox_matches_bitboards());
+    }
+}
+
+#[wasm_bindgen]
+impl Board {
+    pub fn start_pos() -> Board {
+        Self::from_fen(DEFAULT_FEN)
     }
 }
 
 impl Board {
     pub fn get(&self, idx: u64) -> bool {
-        self.w_pawn.get(idx)
-            || self.w_knight.get(idx)
-            || self.w_bishop.get(idx)
-            || self.w_rook.get(idx)
-            || self.w_queen.get(idx)
-            || self.w_king.get(idx)
-            || self.b_pawn.get(idx)
-            || self.b_knight.get(idx)
-            || self.b_bishop.get(idx)
-            || self.b_rook.get(idx)
-            || self.b_queen.get(idx)
-            || self.b_king.get(idx)
+        self.mailbox[idx].is_some()
     }
 
     pub fn piece(&self, idx: u64) -> Option<Piece> {
-        if self.w_pawn.get(idx) {
-            return Some(Piece::new(PieceKind::Pawn, Color::White));
-        }
-        if self.w_knight.get(idx) {
-            return Some(Piece::new(PieceKind::Horse, Color::White));
-        }
-        if self.w_bishop.get(idx) {
-            return Some(Piece::new(PieceKind::Bishop, Color::White));
-        }
-        if self.w_rook.get(idx) {
-            return Some(Piece::new(PieceKind::Rook, Color::White));
-        }
-        if self.w_queen.get(idx) {
-            return Some(Piece::new(PieceKind::Queen, Color::White));
-        }
-        if self.w_king.get(idx) {
-            return Some(Piece::new(PieceKind::King, Color::White));
+        self.mailbox[idx]
+    }
+
+    pub fn boards(&self, color: Color) -> [BitBoard; 6] {
+        match color {
+            Color::White => [
+                self.w_pawn,
+                self.w_knight,
+                self.w_bishop,
+                self.w_rook,
+                self.w_queen,
+                self.w_king,
+            ],
+            Color::Black => [
+                self.b_pawn,
+                self.b_knight,
+                self.b_bishop,
+                self.b_rook,
+                self.b_queen,
+                self.b_king,
+            ],
         }
-        if self.b_pawn.get(idx) {
-            return Some(Piece::new(PieceKind::Pawn, Color::Black));
+    }
+
+    fn piece_boards(&self) -> [(Piece, BitBoard); 12] {
+        let w = |kind| Piece::new(kind, Color::White);
+        let b = |kind| Piece::new(kind, Color::Black);
+        [
+            (w(PieceKind::Pawn), self.w_pawn),
+            (w(PieceKind::Horse), self.w_knight),
+            (w(PieceKind::Bishop), self.w_bishop),
+            (w(PieceKind::Rook), self.w_rook),
+            (w(PieceKind::Queen), self.w_queen),
+            (w(PieceKind::King), self.w_king),
+            (b(PieceKind::Pawn), self.b_pawn),
+            (b(PieceKind::Horse), self.b_knight),
+            (b(PieceKind::Bishop), self.b_bishop),
+            (b(PieceKind::Rook), self.b_rook),
+            (b(PieceKind::Queen), self.b_queen),
+            (b(PieceKind::King), self.b_king),
+        ]
+    }
+
+    pub(crate) fn update_mailbox(&mut self) {
+        self.mailbox = Mailbox::default();
+        for (piece, mut bb) in self.piece_boards() {
+            while bb != 0 {
+                self.mailbox[bb.0.trailing_zeros() as u64] = Some(piece);
+                bb &= bb - 1;
+            }
         }
-        if self.b_knight.get(idx) {
-            return Some(Piece::new(PieceKind::Horse, Color::Black));
+    }
+
+    pub fn mailbox_matches_bitboards(&self) -> bool {
+        let mut boards = [BitBoard(0); 12];
+        for sq in 0..64u64 {
+            if let Some(p) = self.mailbox[sq] {
+                boards[p.color as usize * 6 + p.kind as usize].set(sq);
+            }
         }
-        if self.b_bishop.get(idx) {
-            return Some(Piece::new(PieceKind::Bishop, Color::Black));
+        self.piece_boards()
+            .into_iter()
+            .zip(boards)
+            .all(|((_, bb), mb)| bb == mb)
+    }
+}
+
+impl Board {
+    pub fn compute_hash(&self) -> u64 {
+        let mut hash = 0;
+        for i in 0..64 {
+            if let Some(p) = self.piece(i) {
+                hash ^= ZOBRIST.piece(p, i);
+            }
         }
-        if self.b_rook.get(idx) {
-            return Some(Piece::new(PieceKind::Rook, Color::Black));
+        hash ^= ZOBRIST.castling[self.can_castle as usize];
+        if self.side_to_move == Color::Black {
+            hash ^= ZOBRIST.side_to_move;
         }
-        if self.b_queen.get(idx) {
-            return Some(Piece::new(PieceKind::Queen, Color::Black));
+        hash ^ self.en_passant_hash()
+    }
+
+    // The en passant file only takes part in the hash when the side to move
+    // can legally capture on it, so positions that differ only by an unusable
+    // en passant square share a key.
+    fn en_passant_hash(&self) -> u64 {
+        match self.en_passant {
+            Some(ep) if self.can_capture_en_passant() => ZOBRIST.en_passant[ep.file() as usize],
+            _ => 0,
         }
-        if self.b_king.get(idx) {
-            return Some(Piece::new(PieceKind::King, Color::Black));
+    }
+
+    fn can_capture_en_passant(&self) -> bool {
+        let Some(ep) = self.en_passant else {
+            return false;
+        };
+        let mut capturers = match self.side_to_move {
+            Color::White => PRECALC.pawns[ep.idx_usize()][1] & self.w_pawn,
+            Color::Black => PRECALC.pawns[ep.idx_usize()][0] & self.b_pawn,
+        };
+        let king = self.boards(self.side_to_move)[5];
+        while capturers != 0 {
+            let from = Square::from(capturers.0.trailing_zeros() as u64);
+            capturers &= capturers - 1;
+            // Without a king there is nothing to check against; `validate`
+            // rejects such positions anyway.
+            if king == 0
+                || en_passant_legal(self, Square::from(king.0.trailing_zeros() as u64), from, ep)
+            {
+                return true;
+            }
         }
-        None
+        false
     }
-}
 
-impl Board {
-    fn update_occ(&mut self) {
+    pub(crate) fn update_occ(&mut self) {
         self.w_occ =
             self.w_pawn | self.w_knight | self.w_bishop | self.w_rook | self.w_queen | self.w_king;
         self.b_occ =
@@ -379,7 +716,15 @@ impl Board {
 #[wasm_bindgen]
 impl Board {
     pub fn update_state(&mut self) {
-        let moves = legal_moves(self);
+        // A claimed draw ends the game, whatever the position says.
+        if self.draw_claimed {
+            return;
+        }
+        self.game_state = GameState::InProgress;
+        self.draw_reason = None;
+        self.claimable_draw = None;
+
+        let moves = generate_moves(self);
         if moves.is_empty() {
             let king = self.boards_color(self.side_to_move)[5].0.trailing_zeros() as u64;
             if square_attacked(self, king, self.side_to_move.opposite()) {
@@ -387,10 +732,131 @@ impl Board {
             } else {
                 self.game_state = GameState::Stalemate;
             }
+            return;
+        }
+
+        let repetitions = self.repetitions();
+        if self.insufficient_material() {
+            self.game_state = GameState::Draw;
+            self.draw_reason = Some(DrawReason::InsufficientMaterial);
+        } else if self.blocked_position() {
+            self.game_state = GameState::Draw;
+            self.draw_reason = Some(DrawReason::DeadPosition);
+        } else if repetitions >= 5 {
+            self.game_state = GameState::Draw;
+            self.draw_reason = Some(DrawReason::FivefoldRepetition);
+        } else if self.halfmove_clock >= SEVENTY_FIVE_MOVE_RULE {
+            self.game_state = GameState::Draw;
+            self.draw_reason = Some(DrawReason::SeventyFiveMoveRule);
+        } else if repetitions >= 3 {
+            self.claimable_draw = Some(DrawReason::ThreefoldRepetition);
+        } else if self.halfmove_clock >= FIFTY_MOVE_RULE {
+            self.claimable_draw = Some(DrawReason::FiftyMoveRule);
+        }
+    }
+
+    /// Number of times the current position has occurred, counting itself.
+    /// Only positions since the last capture or pawn move can repeat.
+    pub fn repetitions(&self) -> u32 {
+        let reversible = (self.halfmove_clock as usize).min(self.history.len());
+        self.history
+            .iter()
+            .rev()
+            .take(reversible)
+            .skip(1)
+            .step_by(2)
+            .filter(|&&h| h == self.hash)
+            .count() as u32
+            + 1
+    }
+
+    /// Ends the game if a draw can be claimed. No more moves are accepted
+    /// until the move before the claim is taken back.
+    pub fn claim_draw(&mut self) -> bool {
+        match self.claimable_draw {
+            Some(reason) if self.game_state == GameState::InProgress => {
+                self.game_state = GameState::Draw;
+                self.draw_reason = Some(reason);
+                self.draw_claimed = true;
+                true
+            }
+            _ => false,
         }
     }
 }
 
+impl Board {
+    /// No pawns or major pieces and at most one minor piece, or only bishops
+    /// that all stand on squares of the same color.
+    pub fn insufficient_material(&self) -> bool {
+        let pawns_and_majors =
+            self.w_pawn | self.w_rook | self.w_queen | self.b_pawn | self.b_rook | self.b_queen;
+        if pawns_and_majors != 0 {
+            return false;
+        }
+
+        let knights = self.w_knight | self.b_knight;
+        let bishops = self.w_bishop | self.b_bishop;
+        if (knights | bishops).0.count_ones() <= 1 {
+            return true;
+        }
+        knights == 0 && (bishops & LIGHT_SQUARES == 0 || bishops & DARK_SQUARES == 0)
+    }
+
+    /// Only kings and pawns are left, every pawn is blocked with no capture
+    /// available, en passant included, and neither king can walk to an undefended enemy pawn.
+    pub fn blocked_position(&self) -> bool {
+        let pieces = self.w_knight
+            | self.w_bishop
+            | self.w_rook
+            | self.w_queen
+            | self.b_knight
+            | self.b_bishop
+            | self.b_rook
+            | self.b_queen;
+        let pawns = self.w_pawn | self.b_pawn;
+        if pieces != 0 || pawns == 0 || self.can_capture_en_passant() {
+            return false;
+        }
+
+        if (self.w_pawn << 8) & !pawns != 0 || (self.b_pawn >> 8) & !pawns != 0 {
+            return false;
+        }
+
+        let w_attacks = pawn_attacks(self.w_pawn, Color::White);
+        let b_attacks = pawn_attacks(self.b_pawn, Color::Black);
+        if w_attacks & self.b_pawn != 0 || b_attacks & self.w_pawn != 0 {
+            return false;
+        }
+
+        let w_reach = king_reach(self.w_king, !pawns & !b_attacks);
+        let b_reach = king_reach(self.b_king, !pawns & !w_attacks);
+        king_attacks(w_reach) & self.b_pawn & !b_attacks == 0
+            && king_attacks(b_reach) & self.w_pawn & !w_attacks == 0
+    }
+}
+
+/// Squares the king can reach by walking only through `passable` squares.
+fn king_reach(king: BitBoard, passable: BitBoard) -> BitBoard {
+    let mut reach = king;
+    loop {
+        let next = reach | (king_attacks(reach) & passable);
+        if next == reach {
+            return reach;
+        }
+        reach = next;
+    }
+}
+
+fn king_attacks(mut squares: BitBoard) -> BitBoard {
+    let mut attacks = BitBoard(0);
+    while squares != 0 {
+        attacks |= PRECALC.king[squares.0.trailing_zeros() as usize];
+        squares &= squares - 1;
+    }
+    attacks
+}
+
 impl std::fmt::Display for Board {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         for i in 0..8 {
@@ -426,7 +892,7 @@ impl std::fmt::Display for Board {
         }
         writeln!(f, "\n     a b c d e f g h")?;
         writeln!(f, "  Side to move: {:?}", self.side_to_move)?;
-        writeln!(f, "  En passant: {:?}", self.en_passant.map(Square::from))?;
+        writeln!(f, "  En passant: {:?}", self.en_passant)?;
         write!(f, "  Can castle: ")?;
         [
             (Castle::WhiteKing, "K"),
@@ -0,0 +1,277 @@
+use crate::{
+    board::{Board, Castle},
+    piece::Color,
+    square::Square,
+    validate::PositionError,
+};
+use std::fmt::Display;
+use wasm_bindgen::prelude::*;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FenError {
+    MissingField(FenField),
+    RankCount(usize),
+    RankLength { rank: usize, len: usize },
+    InvalidPiece { square: Square, c: char },
+    InvalidSideToMove(String),
+    InvalidCastling(char),
+    InvalidEnPassant(String),
+    InvalidHalfmoveClock(String),
+    InvalidFullmoveNumber(String),
+    TrailingField(String),
+    IllegalPosition(Vec<PositionError>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FenField {
+    PiecePlacement,
+    SideToMove,
+    Castling,
+    EnPassant,
+}
+
+impl Display for FenField {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::PiecePlacement => write!(f, "piece placement"),
+            Self::SideToMove => write!(f, "side to move"),
+            Self::Castling => write!(f, "castling availability"),
+            Self::EnPassant => write!(f, "en passant target square"),
+        }
+    }
+}
+
+impl Display for FenError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MissingField(field) => write!(f, "missing {} field", field),
+            Self::RankCount(n) => write!(f, "expected 8 ranks, found {}", n),
+            Self::RankLength { rank, len } => {
+                write!(f, "rank {} has {} squares, expected 8", rank, len)
+            }
+            Self::InvalidPiece { square, c } => {
+                write!(f, "invalid piece '{}' on square {}", c, square)
+            }
+            Self::InvalidSideToMove(s) => write!(f, "invalid side to move '{}'", s),
+            Self::InvalidCastling(c) => write!(f, "invalid castling character '{}'", c),
+            Self::InvalidEnPassant(s) => write!(f, "invalid en passant square '{}'", s),
+            Self::InvalidHalfmoveClock(s) => write!(f, "invalid halfmove clock '{}'", s),
+            Self::InvalidFullmoveNumber(s) => write!(f, "invalid fullmove number '{}'", s),
+            Self::TrailingField(s) => write!(f, "unexpected field '{}' after fullmove number", s),
+            Self::IllegalPosition(errors) => {
+                write!(f, "illegal position: ")?;
+                for (i, e) in errors.iter().enumerate() {
+                    if i != 0 {
+                        write!(f, "; ")?;
+                    }
+                    write!(f, "{}", e)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for FenError {}
+
+impl Board {
+    pub fn try_from_fen(fen: &str) -> Result<Board, FenError> {
+        let mut s = Self::default();
+
+        let mut parts = fen.split_whitespace();
+
+        // Piece placement
+        let board = parts
+            .next()
+            .ok_or(FenError::MissingField(FenField::PiecePlacement))?;
+        let ranks = board.split('/').collect::<Vec<_>>();
+        if ranks.len() != 8 {
+            return Err(FenError::RankCount(ranks.len()));
+        }
+        for (i, l) in ranks.into_iter().enumerate() {
+            let i = 7 - i;
+            let mut j = 0;
+            for c in l.chars() {
+                if j >= 8 {
+                    return Err(FenError::RankLength {
+                        rank: i + 1,
+                        len: j + 1,
+                    });
+                }
+                let idx = (i * 8 + j) as u64;
+                match c {
+                    '1'..='8' => j += c.to_digit(10).unwrap() as usize - 1,
+                    'p' => s.b_pawn.set(idx),
+                    'n' => s.b_knight.set(idx),
+                    'b' => s.b_bishop.set(idx),
+                    'r' => s.b_rook.set(idx),
+                    'q' => s.b_queen.set(idx),
+                    'k' => s.b_king.set(idx),
+                    'P' => s.w_pawn.set(idx),
+                    'N' => s.w_knight.set(idx),
+                    'B' => s.w_bishop.set(idx),
+                    'R' => s.w_rook.set(idx),
+                    'Q' => s.w_queen.set(idx),
+                    'K' => s.w_king.set(idx),
+                    _ => {
+                        return Err(FenError::InvalidPiece {
+                            square: Square::from(idx),
+                            c,
+                        })
+                    }
+                }
+                j += 1;
+            }
+            if j != 8 {
+                return Err(FenError::RankLength {
+                    rank: i + 1,
+                    len: j,
+                });
+            }
+        }
+
+        // Side to move
+        match parts.next() {
+            Some("w") => s.side_to_move = Color::White,
+            Some("b") => s.side_to_move = Color::Black,
+            Some(x) => return Err(FenError::InvalidSideToMove(x.to_string())),
+            None => return Err(FenError::MissingField(FenField::SideToMove)),
+        };
+
+        // Castling availability
+        let castling = parts
+            .next()
+            .ok_or(FenError::MissingField(FenField::Castling))?;
+        if castling != "-" {
+            for c in castling.chars() {
+                let right = match c {
+                    'K' => Castle::WhiteKing,
+                    'Q' => Castle::WhiteQueen,
+                    'k' => Castle::BlackKing,
+                    'q' => Castle::BlackQueen,
+                    _ => return Err(FenError::InvalidCastling(c)),
+                } as u8;
+                if s.can_castle & right != 0 {
+                    return Err(FenError::InvalidCastling(c));
+                }
+                s.can_castle |= right;
+            }
+        }
+
+        // En passant target square
+        let en_passant = parts
+            .next()
+            .ok_or(FenError::MissingField(FenField::EnPassant))?;
+        if en_passant != "-" {
+            match en_passant.as_bytes() {
+                &[file @ b'a'..=b'h', rank @ (b'3' | b'6')] => {
+                    let (file, rank) = ((file - b'a') as u64, (rank - b'1') as u64);
+                    s.en_passant = Some(Square::from(rank * 8 + file));
+                }
+                _ => return Err(FenError::InvalidEnPassant(en_passant.to_string())),
+            }
+        }
+
+        // Halfmove clock
+        if let Some(x) = parts.next() {
+            s.halfmove_clock = x
+                .parse()
+                .map_err(|_| FenError::InvalidHalfmoveClock(x.to_string()))?;
+        }
+
+        // Fullmove number
+        s.fullmove_number = 1;
+        if let Some(x) = parts.next() {
+            s.fullmove_number = match x.parse() {
+                Ok(n) if n > 0 => n,
+                _ => return Err(FenError::InvalidFullmoveNumber(x.to_string())),
+            };
+        }
+        if let Some(x) = parts.next() {
+            return Err(FenError::TrailingField(x.to_string()));
+        }
+
+        s.update_occ();
+        s.update_mailbox();
+        s.hash = s.compute_hash();
+        Ok(s)
+    }
+
+    /// Panics on an invalid FEN. Not exported to JS, where a panic takes the
+    /// whole wasm instance down.
+    pub fn from_fen(fen: &str) -> Board {
+        Self::try_from_fen(fen).expect("invalid FEN")
+    }
+}
+
+#[wasm_bindgen]
+impl Board {
+    /// The only way to build a board from a FEN in JS. Always strict, since
+    /// move generation assumes both sides have exactly one king.
+    #[wasm_bindgen(js_name = try_from_fen)]
+    pub fn try_from_fen_js(fen: &str) -> Result<Board, JsError> {
+        Ok(Self::try_from_fen_strict(fen)?)
+    }
+
+    pub fn to_fen(&self) -> String {
+        let mut fen = String::new();
+
+        // Piece placement
+        for rank in (0..8).rev() {
+            let mut empty = 0;
+            for file in 0..8 {
+                match self.piece(rank * 8 + file) {
+                    Some(p) => {
+                        if empty != 0 {
+                            fen.push_str(&empty.to_string());
+                            empty = 0;
+                        }
+                        match p.color {
+                            Color::White => fen.push_str(&p.kind.letter().to_uppercase()),
+                            Color::Black => fen.push_str(p.kind.letter()),
+                        }
+                    }
+                    None => empty += 1,
+                }
+            }
+            if empty != 0 {
+                fen.push_str(&empty.to_string());
+            }
+            if rank != 0 {
+                fen.push('/');
+            }
+        }
+
+        // Side to move
+        fen.push_str(match self.side_to_move {
+            Color::White => " w ",
+            Color::Black => " b ",
+        });
+
+        // Castling availability
+        if self.can_castle == 0 {
+            fen.push('-');
+        }
+        for (c, s) in [
+            (Castle::WhiteKing, 'K'),
+            (Castle::WhiteQueen, 'Q'),
+            (Castle::BlackKing, 'k'),
+            (Castle::BlackQueen, 'q'),
+        ] {
+            if self.can_castle & c as u8 != 0 {
+                fen.push(s);
+            }
+        }
+
+        // En passant target square
+        match self.en_passant {
+            Some(sq) => fen.push_str(&format!(" {} ", sq)),
+            None => fen.push_str(" - "),
+        }
+
+        // Halfmove clock and fullmove number
+        fen.push_str(&format!("{} {}", self.halfmove_clock, self.fullmove_number));
+
+        fen
+    }
+}
@@ -1,10 +1,21 @@
+use wasm_bindgen::prelude::*;
+
 pub mod bitboard;
 pub mod bitboardindex;
 pub mod board;
+pub mod fen;
 pub mod movegen;
+pub mod perft;
 pub mod piece;
 pub mod square;
+pub mod validate;
+pub mod zobrist;
 
 pub mod bot;
 
 mod console_log;
+
+#[wasm_bindgen(start)]
+pub fn start() {
+    console_error_panic_hook::set_once();
+}
@@ -1,155 +1,160 @@
-use super::BitBoard;
+// Generated by `cargo run --release --bin magic_generator -- --seed 0 --reduce 1 --tries 1000000`.
+// Do not edit by hand.
 
 pub const ROOK_MAGIC: [u64; 64] = [
-    0x2280004000802410,
-    0x2040400020001000,
-    0x4080200080100109,
-    0x300210010002c08,
-    0x300100208010004,
-    0xa900060881004400,
-    0x1080320017000080,
-    0x808010a100084080,
-    0x1000800024844008,
-    0xc381002104400080,
-    0x1002000423100,
-    0x101001000200900,
-    0x2000820a60010,
-    0x20488002014c0080,
-    0x800200800100,
-    0x2010000ba004100,
-    0x1818000400064,
-    0x500210040010082,
-    0x810008010802000,
-    0x5208008050018028,
-    0x10828008001400,
-    0x3010024001218,
-    0x24010c0010020841,
-    0x4054420000884421,
-    0x201800100204108,
-    0x20002480400082,
-    0x808100080802000,
-    0x4802100080800800,
-    0x4c180100104500,
-    0x26020080800400,
-    0x8012030400063008,
-    0x4080064200040081,
-    0x400224008800880,
-    0x8020802101004004,
-    0x8100080802000,
-    0x8084120042002008,
-    0x4000801800800400,
-    0x400020080802400,
-    0x1002000402001811,
-    0x8c4084a000091,
-    0x2021400920908000,
-    0x6008400481030060,
-    0x102002080420018,
-    0x8040234200320008,
-    0x112140008008080,
-    0x40002008080,
-    0x8012208240010,
-    0xc014084060009,
-    0x800122470100,
-    0x1008610080400500,
-    0x402054120108200,
-    0x8081000910006100,
-    0x101005008000500,
-    0x4000412008080,
-    0x4005110810020400,
-    0x40002100c0840200,
-    0x1103020092208042,
-    0x808320c0013101,
-    0x1006083041002001,
-    0x400205001000825,
-    0x1008402080011,
-    0x4200100b140842,
-    0x908410010208008c,
-    0x8000108ac804402,
+    0x6080004001116080,
+    0x00c00440a0005000,
+    0x61000a4100102000,
+    0x4100100005000920,
+    0x6a00200810040200,
+    0x0100020100080c00,
+    0x2400100081020804,
+    0x1100028032004100,
+    0x1850800061400190,
+    0x0001401003a00140,
+    0x0401802000b00188,
+    0x0200803000824800,
+    0x80120020240a0010,
+    0x0802000430080a00,
+    0x1002800e00800100,
+    0x0041001180490012,
+    0x0c22848000400026,
+    0x4010004004600a41,
+    0x8000510020004100,
+    0x0001010020700408,
+    0x00c0808008018400,
+    0x0004008002002480,
+    0x0000040008310290,
+    0xd801220000841841,
+    0x1002803080004000,
+    0x50201000c0002840,
+    0x882000a080100088,
+    0x028d002100500018,
+    0x0048020040040040,
+    0x0002014200080430,
+    0x00010104001a1008,
+    0x4001802c80024300,
+    0x124000a090800040,
+    0x4323892000804000,
+    0x000508c051002001,
+    0x1211802802803000,
+    0x0000080080800400,
+    0x500200044a000810,
+    0x4820101684000908,
+    0x0a010004810020c6,
+    0x4000400a80648002,
+    0x1000200450014000,
+    0x8410008020028010,
+    0x2404100008008080,
+    0x40c8040008008080,
+    0x002a000400808012,
+    0x2282004401020028,
+    0x000c00a10042000c,
+    0x1080004001802080,
+    0x24201680c0006080,
+    0x4050009282200080,
+    0x00083000a1030900,
+    0x0220241101080100,
+    0x80001200800c0080,
+    0x000088220d100400,
+    0x60c0004409008600,
+    0x0004104480002101,
+    0x0001401081020922,
+    0x0001004008200011,
+    0xc00010008428a101,
+    0x8501001002880045,
+    0x0401003208840041,
+    0x1a0010011208118c,
+    0x0009001200804821,
 ];
 
-pub const BISHOP_MAGIC: [u64; 64] = [
-    0x122601114208080,
-    0xc880210421020,
-    0x88110c0404410002,
-    0x14440280000829,
-    0x2021088010000,
-    0x10900420800520,
-    0x8828080400d0,
-    0x23d1022201244000,
-    0x10a0885065820405,
-    0x4000200901020280,
-    0x5110a81800d08140,
-    0x8404228004c0,
-    0x110c0000082,
-    0x28a10882a020d008,
-    0x540020504200400,
-    0x401102808410820c,
-    0x20a098450040800,
-    0x102000100a020840,
-    0x1022000108010100,
-    0x8404000202120000,
-    0x41000820082020,
-    0x9040020042200e,
-    0x880400b0949410b0,
-    0x201000041180100,
-    0x4810420088020401,
-    0x8084820050300,
-    0x3008040088082760,
-    0x2008008008440,
-    0x8001005009004028,
-    0x80589200010102a4,
-    0x8410002092500,
-    0x1044008028420092,
-    0xc040480413002,
-    0x4082a82004082604,
-    0x62802080900080,
-    0xc0020080080080,
-    0x1040020200386280,
-    0x20a11001000e0900,
-    0x18580440008201,
-    0x2104900020088,
-    0x43a0901008101094,
-    0xa26010420000300,
-    0x30008a0804001200,
-    0x1090884010400200,
-    0x2010a4100c000284,
-    0x2250020a888600,
-    0x2280800884120,
-    0x4404110046026b04,
-    0xc00a009004100c04,
-    0x8004445808280044,
-    0x1001420b00880000,
-    0xc00c2002420200c0,
-    0x40002004242040,
-    0x400c0448020000,
-    0x45905030011140b0,
-    0x5240404046204a1,
-    0x40948410080c00,
-    0x18000201040242c6,
-    0x888001081481800,
-    0x200000004840402,
-    0x28040028202,
-    0x804010024280,
-    0x288810008200,
-    0x402501002008020,
+#[rustfmt::skip]
+pub const ROOK_SHIFT: [u32; 64] = [
+    52, 53, 53, 53, 53, 53, 53, 52,
+    53, 54, 54, 54, 54, 54, 54, 53,
+    53, 54, 54, 54, 54, 54, 54, 53,
+    53, 54, 54, 54, 54, 54, 54, 53,
+    53, 54, 54, 54, 54, 54, 54, 53,
+    53, 54, 54, 54, 54, 54, 54, 53,
+    53, 54, 54, 54, 54, 54, 54, 53,
+    52, 53, 53, 53, 53, 53, 53, 52,
 ];
 
-pub fn occupancy(idx: u64, attacks: BitBoard) -> BitBoard {
-    let mut occ = BitBoard(0);
-    let mut bb = attacks.0;
-    let mut cnt = 0;
-    while bb != 0 {
-        let lsb = lsb_idx(BitBoard(bb));
-        bb &= bb - 1;
-
-        if (idx & (1 << cnt)) != 0 {
-            occ.0 |= 1 << lsb;
-        }
-        cnt += 1;
-    }
-    occ
-}
+pub const BISHOP_MAGIC: [u64; 64] = [
+    0x0020010a28004481,
+    0x0044082091020000,
+    0x80210203820000c0,
+    0xb2080a1023800084,
+    0x0811104000000200,
+    0x0002412020000044,
+    0x0004124802081080,
+    0x0020821100a00400,
+    0x3009400a06043902,
+    0x0040500200852200,
+    0x00208c4102020208,
+    0x0080082280601120,
+    0x0800011040000081,
+    0x00000a2820480000,
+    0x0828508811101010,
+    0x0102008041109000,
+    0x00412c08a2080204,
+    0x0220000808010460,
+    0x000c004800485200,
+    0x800401202c218008,
+    0x3002202400a00000,
+    0x0022004100809440,
+    0x4005064201501201,
+    0x0000e0008a011001,
+    0x006220000a481008,
+    0x8008041420198e10,
+    0x0444084404004400,
+    0x3a84004104030002,
+    0x400408200401a000,
+    0x1001150002100080,
+    0x42044582004a1000,
+    0x0001013041040300,
+    0x2022202004102300,
+    0x0828020600098801,
+    0x0006440180900101,
+    0x0080820080080080,
+    0x0a40010050210240,
+    0x11901010410a0900,
+    0x0001030901420800,
+    0x0021020200012306,
+    0x2002191042000800,
+    0x20c082211002a008,
+    0x0049008140501408,
+    0x8404065414020800,
+    0x0000040092040400,
+    0x004d02080200a040,
+    0x8020c802008b0042,
+    0x0010040100320440,
+    0x0804480414200502,
+    0x0004406410280000,
+    0x0040404404040089,
+    0x410000102088102a,
+    0x9004501282020042,
+    0x002c200507120000,
+    0x6040040106020040,
+    0x0224010803090400,
+    0x60004040501010ca,
+    0x8000110103100201,
+    0x20002c0044044420,
+    0x8048040000420200,
+    0x0020280006208200,
+    0x4020332084010200,
+    0x4282840408280311,
+    0x3020880210802200,
+];
 
-fn lsb_idx(bb: BitBoard) -> u32 {
-    bb.0.trailing_zeros()
-}
+#[rustfmt::skip]
+pub const BISHOP_SHIFT: [u32; 64] = [
+    58, 59, 59, 59, 59, 59, 59, 58,
+    59, 59, 59, 59, 59, 59, 59, 59,
+    59, 59, 57, 57, 57, 57, 59, 59,
+    59, 59, 57, 55, 55, 57, 59, 59,
+    59, 59, 57, 55, 55, 57, 59, 59,
+    59, 59, 57, 57, 57, 57, 59, 59,
+    59, 59, 59, 59, 59, 59, 59, 59,
+    58, 59, 59, 59, 59, 59, 59, 58,
+];
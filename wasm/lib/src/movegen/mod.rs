@@ -1,9 +1,8 @@
-use self::precalc::PRECALC;
+use self::{movelist::MoveList, packed::PackedMove, precalc::PRECALC};
 use crate::{
     bitboard::*,
     bitboardindex::BitBoardIdx,
     board::{Board, Castle},
-    console_log,
     piece::{Color, Piece, PieceKind},
     square::Square,
 };
@@ -12,10 +11,12 @@ use std::fmt::Display;
 use wasm_bindgen::prelude::*;
 
 pub mod magic;
+pub mod movelist;
+pub mod packed;
 pub mod precalc;
 
 #[wasm_bindgen]
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Move {
     pub from: Square,
     pub to: Square,
@@ -42,6 +43,18 @@ impl Move {
             special,
         }
     }
+
+    pub fn promotion(&self) -> Option<PieceKind> {
+        match self.special {
+            Some(SpecialMove::Promotion(p)) => Some(p),
+            _ => None,
+        }
+    }
+
+    /// 16 bit encoding of the move, see `PackedMove`.
+    pub fn pack(&self) -> u16 {
+        PackedMove::from(*self).0
+    }
 }
 
 impl Display for Move {
@@ -60,7 +73,7 @@ impl Display for Move {
     }
 }
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, tsify::Tsify)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, tsify::Tsify)]
 #[tsify(from_wasm_abi, into_wasm_abi)]
 pub enum SpecialMove {
     Promotion(PieceKind),
@@ -71,43 +84,222 @@ pub enum SpecialMove {
 
 #[wasm_bindgen]
 pub fn legal_moves(board: &Board) -> Box<[Move]> {
-    generate_moves(board)
-        .into_iter()
-        .filter(|m| {
-            let mut b = board.clone();
-            b.make_move(*m)
-        })
-        .collect()
+    generate_moves(board).into()
+}
+
+/// What the side to move has to respect for a move to be legal: pieces giving
+/// check, pieces pinned to the king and squares the king cannot step on.
+#[derive(Debug, Clone, Copy)]
+pub struct MoveMasks {
+    pub king: Square,
+    pub checkers: BitBoard,
+    /// Squares a non-king move has to land on: everything when not in check,
+    /// the checker and the squares between it and the king in single check,
+    /// nothing in double check.
+    pub check_mask: BitBoard,
+    pub pinned: BitBoard,
+    /// Squares attacked by the opponent, with the king removed from the board
+    /// so it cannot step back along the line of a checking slider.
+    pub king_danger: BitBoard,
+}
+
+impl MoveMasks {
+    pub fn new(board: &Board) -> Self {
+        let us = board.side_to_move;
+        let [_, _, _, _, _, king_bb] = board.boards(us);
+        let [pawns, knights, bishops, rooks, queens, _] = board.boards(us.opposite());
+        let (own, their) = match us {
+            Color::White => (board.w_occ, board.b_occ),
+            Color::Black => (board.b_occ, board.w_occ),
+        };
+        let king = Square::from(king_bb.0.trailing_zeros() as u64);
+        let diagonal = bishops | queens;
+        let orthogonal = rooks | queens;
+
+        let checkers = (PRECALC.pawns[king as usize][us as usize] & pawns)
+            | (PRECALC.knight[king as usize] & knights)
+            | (PRECALC.bishop_attack(king, board.occ) & diagonal)
+            | (PRECALC.rook_attack(king, board.occ) & orthogonal);
+        let check_mask = match checkers.0.count_ones() {
+            0 => BitBoard(!0),
+            1 => checkers | PRECALC.between[king as usize][checkers.0.trailing_zeros() as usize],
+            _ => BitBoard(0),
+        };
+
+        let mut pinned = BitBoard(0);
+        let mut snipers = (PRECALC.bishop_attack(king, their) & diagonal)
+            | (PRECALC.rook_attack(king, their) & orthogonal);
+        while snipers != 0 {
+            let sniper = snipers.0.trailing_zeros() as usize;
+            let blockers = PRECALC.between[king as usize][sniper] & board.occ;
+            if blockers.0.count_ones() == 1 && blockers & own != 0 {
+                pinned |= blockers;
+            }
+            snipers &= snipers - 1;
+        }
+
+        let king_danger = attacks(board, us.opposite(), board.occ & !king_bb);
+
+        Self {
+            king,
+            checkers,
+            check_mask,
+            pinned,
+            king_danger,
+        }
+    }
+
+    /// Squares a non-king piece on `from` may move to.
+    pub fn allowed(&self, from: Square) -> BitBoard {
+        if self.pinned.get(from) {
+            self.check_mask & PRECALC.line[self.king as usize][from as usize]
+        } else {
+            self.check_mask
+        }
+    }
+}
+
+/// Which part of the legal moves to generate. `Captures` and `Quiets` split
+/// the full list in two: promotions count as captures so quiescence search
+/// sees them, castling counts as quiet. `Evasions` is the full list when in
+/// check and nothing otherwise.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GenMode {
+    All,
+    Captures,
+    Quiets,
+    Evasions,
+}
+
+impl GenMode {
+    pub fn captures(self) -> bool {
+        self != GenMode::Quiets
+    }
+
+    pub fn quiets(self) -> bool {
+        self != GenMode::Captures
+    }
+
+    fn capture_mask(self) -> BitBoard {
+        BitBoard(if self.captures() { !0 } else { 0 })
+    }
+
+    fn quiet_mask(self) -> BitBoard {
+        BitBoard(if self.quiets() { !0 } else { 0 })
+    }
+
+    /// Pushes onto `promotion_rank` promote, so they go with the captures.
+    fn push_mask(self, promotion_rank: u64) -> BitBoard {
+        (self.capture_mask() & promotion_rank) | (self.quiet_mask() & !promotion_rank)
+    }
+}
+
+pub fn generate_moves(board: &Board) -> MoveList {
+    generate_moves_mode(board, GenMode::All)
 }
 
-pub fn generate_moves(board: &Board) -> Vec<Move> {
-    let mut vmoves = Vec::new();
-    vmoves.reserve_exact(220);
+pub fn generate_moves_mode(board: &Board, mode: GenMode) -> MoveList {
+    let mut vmoves = MoveList::new();
+    let masks = MoveMasks::new(board);
+    if mode == GenMode::Evasions && masks.checkers == 0 {
+        return vmoves;
+    }
 
     if board.side_to_move == Color::White {
-        white_pawn_moves(board, &mut vmoves);
-        white_castle(board, &mut vmoves);
-        knight_moves(board, board.w_knight, board.w_occ, board.b_occ, &mut vmoves);
-        king_moves(board, board.w_king, board.w_occ, board.b_occ, &mut vmoves);
-        bishop_moves(board, board.w_bishop, board.b_occ, false, &mut vmoves);
-        rook_moves(board, board.w_rook, board.b_occ, false, &mut vmoves);
-        queen_moves(board, board.w_queen, board.b_occ, &mut vmoves);
+        pawn_moves::<White>(board, &masks, mode, &mut vmoves);
+        castle_moves::<White>(board, &masks, mode, &mut vmoves);
+        knight_moves(
+            board,
+            board.w_knight,
+            board.w_occ,
+            board.b_occ,
+            &masks,
+            mode,
+            &mut vmoves,
+        );
+        king_moves(
+            board,
+            board.w_king,
+            board.w_occ,
+            board.b_occ,
+            &masks,
+            mode,
+            &mut vmoves,
+        );
+        bishop_moves(
+            board,
+            board.w_bishop,
+            board.b_occ,
+            false,
+            &masks,
+            mode,
+            &mut vmoves,
+        );
+        rook_moves(
+            board,
+            board.w_rook,
+            board.b_occ,
+            false,
+            &masks,
+            mode,
+            &mut vmoves,
+        );
+        queen_moves(board, board.w_queen, board.b_occ, &masks, mode, &mut vmoves);
     } else {
-        black_pawn_moves(board, &mut vmoves);
-        black_castle(board, &mut vmoves);
-        knight_moves(board, board.b_knight, board.b_occ, board.w_occ, &mut vmoves);
-        king_moves(board, board.b_king, board.b_occ, board.w_occ, &mut vmoves);
-        bishop_moves(board, board.b_bishop, board.w_occ, false, &mut vmoves);
-        rook_moves(board, board.b_rook, board.w_occ, false, &mut vmoves);
-        queen_moves(board, board.b_queen, board.w_occ, &mut vmoves);
+        pawn_moves::<Black>(board, &masks, mode, &mut vmoves);
+        castle_moves::<Black>(board, &masks, mode, &mut vmoves);
+        knight_moves(
+            board,
+            board.b_knight,
+            board.b_occ,
+            board.w_occ,
+            &masks,
+            mode,
+            &mut vmoves,
+        );
+        king_moves(
+            board,
+            board.b_king,
+            board.b_occ,
+            board.w_occ,
+            &masks,
+            mode,
+            &mut vmoves,
+        );
+        bishop_moves(
+            board,
+            board.b_bishop,
+            board.w_occ,
+            false,
+            &masks,
+            mode,
+            &mut vmoves,
+        );
+        rook_moves(
+            board,
+            board.b_rook,
+            board.w_occ,
+            false,
+            &masks,
+            mode,
+            &mut vmoves,
+        );
+        queen_moves(board, board.b_queen, board.w_occ, &masks, mode, &mut vmoves);
     }
 
     vmoves
 }
 
-pub fn queen_moves(board: &Board, queens: BitBoard, o_occ: BitBoard, vmoves: &mut Vec<Move>) {
-    rook_moves(board, queens, o_occ, true, vmoves);
-    bishop_moves(board, queens, o_occ, true, vmoves);
+pub fn queen_moves(
+    board: &Board,
+    queens: BitBoard,
+    o_occ: BitBoard,
+    masks: &MoveMasks,
+    mode: GenMode,
+    vmoves: &mut MoveList,
+) {
+    rook_moves(board, queens, o_occ, true, masks, mode, vmoves);
+    bishop_moves(board, queens, o_occ, true, masks, mode, vmoves);
 }
 
 pub fn bishop_moves(
@@ -115,13 +307,15 @@ pub fn bishop_moves(
     mut bishops: BitBoard,
     o_occ: BitBoard,
     queen: bool,
-    vmoves: &mut Vec<Move>,
+    masks: &MoveMasks,
+    mode: GenMode,
+    vmoves: &mut MoveList,
 ) {
     while bishops != 0 {
         let from = Square::from(bishops.0.trailing_zeros() as u64);
-        let moves = PRECALC.bishop_attack(from, board.occ);
-        let mut attacks = moves & o_occ;
-        let mut moves = moves & !board.occ;
+        let moves = PRECALC.bishop_attack(from, board.occ) & masks.allowed(from);
+        let mut attacks = moves & o_occ & mode.capture_mask();
+        let mut moves = moves & !board.occ & mode.quiet_mask();
 
         while moves != 0 {
             let to = Square::from(moves.0.trailing_zeros() as u64);
@@ -169,13 +363,15 @@ pub fn rook_moves(
     mut rooks: BitBoard,
     o_occ: BitBoard,
     queen: bool,
-    vmoves: &mut Vec<Move>,
+    masks: &MoveMasks,
+    mode: GenMode,
+    vmoves: &mut MoveList,
 ) {
     while rooks != 0 {
         let from = Square::from(rooks.0.trailing_zeros() as u64);
-        let moves = PRECALC.rook_attack(from, board.occ);
-        let mut attacks = moves & o_occ;
-        let mut moves = moves & !board.occ;
+        let moves = PRECALC.rook_attack(from, board.occ) & masks.allowed(from);
+        let mut attacks = moves & o_occ & mode.capture_mask();
+        let mut moves = moves & !board.occ & mode.quiet_mask();
 
         while moves != 0 {
             let to = Square::from(moves.0.trailing_zeros() as u64);
@@ -223,13 +419,15 @@ pub fn knight_moves(
     mut knights: BitBoard,
     c_occ: BitBoard,
     o_occ: BitBoard,
-    vmoves: &mut Vec<Move>,
+    masks: &MoveMasks,
+    mode: GenMode,
+    vmoves: &mut MoveList,
 ) {
     while knights != 0 {
         let from = Square::from(knights.0.trailing_zeros() as u64);
-        let moves = PRECALC.knight[from as usize] & !c_occ;
-        let mut attacks = moves & o_occ;
-        let mut moves = moves & !o_occ;
+        let moves = PRECALC.knight[from as usize] & !c_occ & masks.allowed(from);
+        let mut attacks = moves & o_occ & mode.capture_mask();
+        let mut moves = moves & !o_occ & mode.quiet_mask();
 
         while moves != 0 {
             let to = Square::from(moves.0.trailing_zeros() as u64);
@@ -263,13 +461,15 @@ pub fn king_moves(
     mut king: BitBoard,
     c_occ: BitBoard,
     o_occ: BitBoard,
-    vmoves: &mut Vec<Move>,
+    masks: &MoveMasks,
+    mode: GenMode,
+    vmoves: &mut MoveList,
 ) {
     while king != 0 {
         let from = Square::from(king.0.trailing_zeros() as u64);
-        let moves = PRECALC.king[from as usize] & !c_occ;
-        let mut attacks = moves & o_occ;
-        let mut moves = moves & !o_occ;
+        let moves = PRECALC.king[from as usize] & !c_occ & !masks.king_danger;
+        let mut attacks = moves & o_occ & mode.capture_mask();
+        let mut moves = moves & !o_occ & mode.quiet_mask();
 
         while moves != 0 {
             let to = Square::from(moves.0.trailing_zeros() as u64);
@@ -298,195 +498,139 @@ pub fn king_moves(
     }
 }
 
-pub fn white_castle(board: &Board, vmoves: &mut Vec<Move>) {
-    castle(
-        board,
-        Castle::WhiteKing,
-        &[Square::F1, Square::G1],
-        Color::White,
-        vmoves,
-    );
-    castle(
-        board,
-        Castle::WhiteQueen,
-        &[Square::D1, Square::C1, Square::B1],
-        Color::White,
-        vmoves,
-    );
+/// Per color constants for the pawn and castling generators. Both are generic
+/// over it so each color gets its own monomorphized copy.
+pub trait Side {
+    const COLOR: Color;
+    /// Rank a pawn lands on after a single push from its start rank.
+    const PUSH_RANK: u64;
+    const PROMOTION_RANK: u64;
+    const KING_START: Square;
+    /// Castling right, squares that must be empty and the king destination.
+    /// The first two squares are the ones the king crosses.
+    const CASTLES: [(u8, &'static [Square]); 2];
+
+    fn forward(bb: BitBoard) -> BitBoard;
 }
 
-pub fn black_castle(board: &Board, vmoves: &mut Vec<Move>) {
-    castle(
-        board,
-        Castle::BlackKing,
-        &[Square::F8, Square::G8],
-        Color::Black,
-        vmoves,
-    );
-    castle(
-        board,
-        Castle::BlackQueen,
-        &[Square::D8, Square::C8, Square::B8],
-        Color::Black,
-        vmoves,
-    );
+pub struct White;
+pub struct Black;
+
+impl Side for White {
+    const COLOR: Color = Color::White;
+    const PUSH_RANK: u64 = RANK_3;
+    const PROMOTION_RANK: u64 = RANK_8;
+    const KING_START: Square = Square::E1;
+    const CASTLES: [(u8, &'static [Square]); 2] = [
+        (Castle::WhiteKing as u8, &[Square::F1, Square::G1]),
+        (
+            Castle::WhiteQueen as u8,
+            &[Square::D1, Square::C1, Square::B1],
+        ),
+    ];
+
+    fn forward(bb: BitBoard) -> BitBoard {
+        bb << 8
+    }
+}
+
+impl Side for Black {
+    const COLOR: Color = Color::Black;
+    const PUSH_RANK: u64 = RANK_6;
+    const PROMOTION_RANK: u64 = RANK_1;
+    const KING_START: Square = Square::E8;
+    const CASTLES: [(u8, &'static [Square]); 2] = [
+        (Castle::BlackKing as u8, &[Square::F8, Square::G8]),
+        (
+            Castle::BlackQueen as u8,
+            &[Square::D8, Square::C8, Square::B8],
+        ),
+    ];
+
+    fn forward(bb: BitBoard) -> BitBoard {
+        bb >> 8
+    }
 }
 
-fn castle(
+pub fn castle_moves<S: Side>(
     board: &Board,
-    castle_bit: Castle,
-    squares: &[Square],
-    color: Color,
-    vmoves: &mut Vec<Move>,
+    masks: &MoveMasks,
+    mode: GenMode,
+    vmoves: &mut MoveList,
 ) {
-    if board.can_castle & castle_bit as u8 != 0 {
-        if color == Color::White {
-            if square_attacked(board, Square::E1, Color::Black) {
-                return;
-            }
-        } else if square_attacked(board, Square::E8, Color::White) {
-            return;
+    if masks.checkers != 0 || !mode.quiets() {
+        return;
+    }
+
+    for (castle_bit, squares) in S::CASTLES {
+        if board.can_castle & castle_bit == 0 {
+            continue;
         }
 
-        let squares_empty = squares
-            .iter()
-            .all(|&s| board.occ & BitBoard(1 << s as u64) == 0);
-        let squares_attacked = squares
-            .iter()
-            .take(2)
-            .any(|&s| square_attacked(board, s, color.opposite()));
+        let squares_empty = squares.iter().all(|&s| !board.occ.get(s));
+        let squares_attacked = squares.iter().take(2).any(|&s| masks.king_danger.get(s));
 
         if squares_empty && !squares_attacked {
             vmoves.push(Move {
-                from: if color == Color::White {
-                    Square::E1
-                } else {
-                    Square::E8
-                },
+                from: S::KING_START,
                 to: squares[1],
                 capture: false,
-                piece: Piece::new(PieceKind::King, color),
+                piece: Piece::new(PieceKind::King, S::COLOR),
                 special: Some(SpecialMove::Castle),
             });
         }
     }
 }
 
-pub fn white_pawn_moves(board: &Board, vmoves: &mut Vec<Move>) {
-    let mut pawns = board.w_pawn;
+pub fn pawn_moves<S: Side>(board: &Board, masks: &MoveMasks, mode: GenMode, vmoves: &mut MoveList) {
+    let [mut pawns, ..] = board.boards(S::COLOR);
+    let their = match S::COLOR {
+        Color::White => board.b_occ,
+        Color::Black => board.w_occ,
+    };
     let occ = board.occ;
+    let piece = Piece::new(PieceKind::Pawn, S::COLOR);
 
     while pawns != 0 {
         let from = Square::from(pawns.0.trailing_zeros() as u64);
         let pawn = BitBoard(1 << pawns.0.trailing_zeros());
         pawns &= pawns - 1;
 
-        let one_rank_up = pawn << 8 & !occ;
-        let two_ranks_up = (one_rank_up & RANK_3) << 8 & !occ;
-        let mut moves = one_rank_up | two_ranks_up;
+        let one_rank_up = S::forward(pawn) & !occ;
+        let two_ranks_up = S::forward(one_rank_up & S::PUSH_RANK) & !occ;
+        let allowed = masks.allowed(from);
 
+        let mut moves = (one_rank_up | two_ranks_up) & allowed & mode.push_mask(S::PROMOTION_RANK);
         while moves != 0 {
             let to = Square::from(moves.0.trailing_zeros() as u64);
-            if to.rank() == 7 {
-                vmoves.push(Move {
-                    from,
-                    to,
-                    capture: false,
-                    piece: Piece::new(PieceKind::Pawn, Color::White),
-                    special: Some(SpecialMove::Promotion(PieceKind::Queen)),
-                });
-                vmoves.push(Move {
-                    from,
-                    to,
-                    capture: false,
-                    piece: Piece::new(PieceKind::Pawn, Color::White),
-                    special: Some(SpecialMove::Promotion(PieceKind::Rook)),
-                });
-                vmoves.push(Move {
-                    from,
-                    to,
-                    capture: false,
-                    piece: Piece::new(PieceKind::Pawn, Color::White),
-                    special: Some(SpecialMove::Promotion(PieceKind::Bishop)),
-                });
-                vmoves.push(Move {
-                    from,
-                    to,
-                    capture: false,
-                    piece: Piece::new(PieceKind::Pawn, Color::White),
-                    special: Some(SpecialMove::Promotion(PieceKind::Horse)),
-                });
-            } else {
-                vmoves.push(Move {
-                    from,
-                    to,
-                    capture: false,
-                    piece: Piece::new(PieceKind::Pawn, Color::White),
-                    special: if from.rank() == 1 && to.rank() == 3 {
-                        Some(SpecialMove::DoublePawnPush)
-                    } else {
-                        None
-                    },
-                });
-            }
             moves &= moves - 1;
+            let special = if two_ranks_up.get(to) {
+                Some(SpecialMove::DoublePawnPush)
+            } else {
+                None
+            };
+            pawn_move::<S>(from, to, piece, false, special, vmoves);
         }
 
-        let mut attacks = PRECALC.pawns[from.idx_usize()][0] & board.b_occ;
+        let mut attacks = PRECALC.pawns[from.idx_usize()][S::COLOR as usize]
+            & their
+            & allowed
+            & mode.capture_mask();
         while attacks != 0 {
             let to = Square::from(attacks.0.trailing_zeros() as u64);
-            if to.rank() == 7 {
-                vmoves.push(Move {
-                    from,
-                    to,
-                    capture: true,
-                    piece: Piece::new(PieceKind::Pawn, Color::White),
-                    special: Some(SpecialMove::Promotion(PieceKind::Queen)),
-                });
-                vmoves.push(Move {
-                    from,
-                    to,
-                    capture: true,
-                    piece: Piece::new(PieceKind::Pawn, Color::White),
-                    special: Some(SpecialMove::Promotion(PieceKind::Rook)),
-                });
-                vmoves.push(Move {
-                    from,
-                    to,
-                    capture: true,
-                    piece: Piece::new(PieceKind::Pawn, Color::White),
-                    special: Some(SpecialMove::Promotion(PieceKind::Bishop)),
-                });
-                vmoves.push(Move {
-                    from,
-                    to,
-                    capture: true,
-                    piece: Piece::new(PieceKind::Pawn, Color::White),
-                    special: Some(SpecialMove::Promotion(PieceKind::Horse)),
-                });
-            } else {
-                vmoves.push(Move {
-                    from,
-                    to,
-                    capture: true,
-                    piece: Piece::new(PieceKind::Pawn, Color::White),
-                    special: if from.rank() == 1 && to.rank() == 3 {
-                        Some(SpecialMove::DoublePawnPush)
-                    } else {
-                        None
-                    },
-                });
-            }
             attacks &= attacks - 1;
+            pawn_move::<S>(from, to, piece, true, None, vmoves);
         }
-        if let Some(ep) = board.en_passant {
-            let epb = BitBoard(1 << ep as u64);
-            if PRECALC.pawns[from.idx_usize()][0] & epb != 0 {
+
+        if let Some(ep) = board.en_passant.filter(|_| mode.captures()) {
+            if PRECALC.pawns[from.idx_usize()][S::COLOR as usize].get(ep)
+                && en_passant_legal(board, masks.king, from, ep)
+            {
                 vmoves.push(Move {
                     from,
                     to: ep,
                     capture: true,
-                    piece: Piece::new(PieceKind::Pawn, Color::White),
+                    piece,
                     special: Some(SpecialMove::EnPassant),
                 });
             }
@@ -494,126 +638,89 @@ pub fn white_pawn_moves(board: &Board, vmoves: &mut Vec<Move>) {
     }
 }
 
-pub fn black_pawn_moves(board: &Board, vmoves: &mut Vec<Move>) {
-    let mut pawns = board.b_pawn;
-    let occ = board.occ;
+/// Pushes a pawn move, expanded into the four promotions on the last rank.
+fn pawn_move<S: Side>(
+    from: Square,
+    to: Square,
+    piece: Piece,
+    capture: bool,
+    special: Option<SpecialMove>,
+    vmoves: &mut MoveList,
+) {
+    if S::PROMOTION_RANK & (1 << to as u64) != 0 {
+        for kind in [
+            PieceKind::Queen,
+            PieceKind::Rook,
+            PieceKind::Bishop,
+            PieceKind::Horse,
+        ] {
+            vmoves.push(Move {
+                from,
+                to,
+                capture,
+                piece,
+                special: Some(SpecialMove::Promotion(kind)),
+            });
+        }
+    } else {
+        vmoves.push(Move {
+            from,
+            to,
+            capture,
+            piece,
+            special,
+        });
+    }
+}
 
-    while pawns != 0 {
-        let from = Square::from(pawns.0.trailing_zeros() as u64);
-        let pawn = BitBoard(1 << pawns.0.trailing_zeros());
-        pawns &= pawns - 1;
+/// En passant removes two pieces from the same rank, so it is checked by
+/// looking at the king after the capture instead of through the pin masks.
+pub(crate) fn en_passant_legal(board: &Board, king: Square, from: Square, ep: Square) -> bool {
+    let us = board.side_to_move;
+    let captured = match us {
+        Color::White => BitBoard(1 << (ep as u64 - 8)),
+        Color::Black => BitBoard(1 << (ep as u64 + 8)),
+    };
+    let occ = (board.occ & !BitBoard(1 << from as u64) & !captured) | BitBoard(1 << ep as u64);
+    let [pawns, knights, bishops, rooks, queens, _] = board.boards(us.opposite());
+    let king = king as usize;
+
+    (PRECALC.pawns[king][us as usize] & pawns & !captured) == 0
+        && (PRECALC.knight[king] & knights) == 0
+        && (PRECALC.bishop_attack(king, occ) & (bishops | queens)) == 0
+        && (PRECALC.rook_attack(king, occ) & (rooks | queens)) == 0
+}
 
-        let one_rank_up = pawn >> 8 & !occ;
-        let two_ranks_up = (one_rank_up & RANK_6) >> 8 & !occ;
-        let mut moves = one_rank_up | two_ranks_up;
+pub fn pawn_attacks(pawns: BitBoard, color: Color) -> BitBoard {
+    match color {
+        Color::White => ((pawns << 7) & !FILE_H) | ((pawns << 9) & !FILE_A),
+        Color::Black => ((pawns >> 9) & !FILE_H) | ((pawns >> 7) & !FILE_A),
+    }
+}
 
-        while moves != 0 {
-            let to = Square::from(moves.0.trailing_zeros() as u64);
-            if to.rank() == 0 {
-                vmoves.push(Move {
-                    from,
-                    to,
-                    capture: false,
-                    piece: Piece::new(PieceKind::Pawn, Color::Black),
-                    special: Some(SpecialMove::Promotion(PieceKind::Queen)),
-                });
-                vmoves.push(Move {
-                    from,
-                    to,
-                    capture: false,
-                    piece: Piece::new(PieceKind::Pawn, Color::Black),
-                    special: Some(SpecialMove::Promotion(PieceKind::Rook)),
-                });
-                vmoves.push(Move {
-                    from,
-                    to,
-                    capture: false,
-                    piece: Piece::new(PieceKind::Pawn, Color::Black),
-                    special: Some(SpecialMove::Promotion(PieceKind::Bishop)),
-                });
-                vmoves.push(Move {
-                    from,
-                    to,
-                    capture: false,
-                    piece: Piece::new(PieceKind::Pawn, Color::Black),
-                    special: Some(SpecialMove::Promotion(PieceKind::Horse)),
-                });
-            } else {
-                vmoves.push(Move {
-                    from,
-                    to,
-                    capture: false,
-                    piece: Piece::new(PieceKind::Pawn, Color::Black),
-                    special: if from.rank() == 6 && to.rank() == 4 {
-                        Some(SpecialMove::DoublePawnPush)
-                    } else {
-                        None
-                    },
-                });
-            }
-            moves &= moves - 1;
-        }
+/// Every square attacked by `side` with the given occupancy.
+pub fn attacks(board: &Board, side: Color, occ: BitBoard) -> BitBoard {
+    let [pawns, mut knights, bishops, rooks, queens, king] = board.boards(side);
 
-        let mut attacks = PRECALC.pawns[from.idx_usize()][1] & board.w_occ;
-        while attacks != 0 {
-            let to = Square::from(attacks.0.trailing_zeros() as u64);
-            if to.rank() == 0 {
-                vmoves.push(Move {
-                    from,
-                    to,
-                    capture: true,
-                    piece: Piece::new(PieceKind::Pawn, Color::Black),
-                    special: Some(SpecialMove::Promotion(PieceKind::Queen)),
-                });
-                vmoves.push(Move {
-                    from,
-                    to,
-                    capture: true,
-                    piece: Piece::new(PieceKind::Pawn, Color::Black),
-                    special: Some(SpecialMove::Promotion(PieceKind::Rook)),
-                });
-                vmoves.push(Move {
-                    from,
-                    to,
-                    capture: true,
-                    piece: Piece::new(PieceKind::Pawn, Color::Black),
-                    special: Some(SpecialMove::Promotion(PieceKind::Bishop)),
-                });
-                vmoves.push(Move {
-                    from,
-                    to,
-                    capture: true,
-                    piece: Piece::new(PieceKind::Pawn, Color::Black),
-                    special: Some(SpecialMove::Promotion(PieceKind::Horse)),
-                });
-            } else {
-                vmoves.push(Move {
-                    from,
-                    to,
-                    capture: true,
-                    piece: Piece::new(PieceKind::Pawn, Color::Black),
-                    special: if from.rank() == 6 && to.rank() == 4 {
-                        Some(SpecialMove::DoublePawnPush)
-                    } else {
-                        None
-                    },
-                });
-            }
-            attacks &= attacks - 1;
-        }
-        if let Some(ep) = board.en_passant {
-            let epb = BitBoard(1 << ep as u64);
-            if PRECALC.pawns[from.idx_usize()][1] & epb != 0 {
-                vmoves.push(Move {
-                    from,
-                    to: ep,
-                    capture: true,
-                    piece: Piece::new(PieceKind::Pawn, Color::Black),
-                    special: Some(SpecialMove::EnPassant),
-                });
-            }
-        }
+    let mut attacks = pawn_attacks(pawns, side);
+    while knights != 0 {
+        attacks |= PRECALC.knight[knights.0.trailing_zeros() as usize];
+        knights &= knights - 1;
+    }
+    let mut diagonal = bishops | queens;
+    while diagonal != 0 {
+        attacks |= PRECALC.bishop_attack(diagonal.0.trailing_zeros() as u64, occ);
+        diagonal &= diagonal - 1;
+    }
+    let mut orthogonal = rooks | queens;
+    while orthogonal != 0 {
+        attacks |= PRECALC.rook_attack(orthogonal.0.trailing_zeros() as u64, occ);
+        orthogonal &= orthogonal - 1;
+    }
+    if king != 0 {
+        attacks |= PRECALC.king[king.0.trailing_zeros() as usize];
     }
+    attacks
 }
 
 pub fn square_attacked(board: &Board, square: impl BitBoardIdx, side: Color) -> bool {
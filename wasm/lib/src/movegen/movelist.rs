@@ -0,0 +1,126 @@
+use super::Move;
+use std::{
+    cmp::Reverse,
+    fmt::Debug,
+    mem::MaybeUninit,
+    ops::{Deref, DerefMut},
+};
+
+/// No position has more than 218 legal moves.
+pub const MAX_MOVES: usize = 256;
+
+/// Fixed capacity move list that lives on the stack.
+pub struct MoveList {
+    moves: [MaybeUninit<Move>; MAX_MOVES],
+    len: usize,
+}
+
+impl MoveList {
+    pub fn new() -> Self {
+        Self {
+            moves: [MaybeUninit::uninit(); MAX_MOVES],
+            len: 0,
+        }
+    }
+
+    pub fn push(&mut self, m: Move) {
+        self.moves[self.len].write(m);
+        self.len += 1;
+    }
+
+    pub fn as_slice(&self) -> &[Move] {
+        // The first `len` moves are always initialized.
+        unsafe { std::slice::from_raw_parts(self.moves.as_ptr() as *const Move, self.len) }
+    }
+
+    pub fn as_mut_slice(&mut self) -> &mut [Move] {
+        unsafe { std::slice::from_raw_parts_mut(self.moves.as_mut_ptr() as *mut Move, self.len) }
+    }
+
+    /// Sorts the moves so the highest scoring ones come first.
+    pub fn sort_by_score<S: Ord>(&mut self, mut score: impl FnMut(&Move) -> S) {
+        self.as_mut_slice()
+            .sort_unstable_by_key(|m| Reverse(score(m)));
+    }
+}
+
+impl Default for MoveList {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clone for MoveList {
+    fn clone(&self) -> Self {
+        Self {
+            moves: self.moves,
+            len: self.len,
+        }
+    }
+}
+
+impl Debug for MoveList {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl Deref for MoveList {
+    type Target = [Move];
+
+    fn deref(&self) -> &[Move] {
+        self.as_slice()
+    }
+}
+
+impl DerefMut for MoveList {
+    fn deref_mut(&mut self) -> &mut [Move] {
+        self.as_mut_slice()
+    }
+}
+
+impl<'a> IntoIterator for &'a MoveList {
+    type Item = &'a Move;
+    type IntoIter = std::slice::Iter<'a, Move>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl IntoIterator for MoveList {
+    type Item = Move;
+    type IntoIter = IntoIter;
+
+    fn into_iter(self) -> IntoIter {
+        IntoIter { list: self, idx: 0 }
+    }
+}
+
+pub struct IntoIter {
+    list: MoveList,
+    idx: usize,
+}
+
+impl Iterator for IntoIter {
+    type Item = Move;
+
+    fn next(&mut self) -> Option<Move> {
+        let m = self.list.get(self.idx).copied();
+        self.idx += 1;
+        m
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.list.len().saturating_sub(self.idx);
+        (len, Some(len))
+    }
+}
+
+impl ExactSizeIterator for IntoIter {}
+
+impl From<MoveList> for Box<[Move]> {
+    fn from(list: MoveList) -> Self {
+        list.as_slice().into()
+    }
+}
@@ -0,0 +1,107 @@
+use super::{Move, SpecialMove};
+use crate::{board::Board, piece::PieceKind, square::Square};
+use wasm_bindgen::prelude::*;
+
+/// A move squeezed into 16 bits: `from` in bits 0-5, `to` in bits 6-11 and
+/// a flag in bits 12-15. The moving piece is not stored, it is read from the
+/// board when unpacking.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct PackedMove(pub u16);
+
+const QUIET: u16 = 0;
+const DOUBLE_PAWN_PUSH: u16 = 1;
+const CASTLE: u16 = 2;
+const CAPTURE: u16 = 4;
+const EN_PASSANT: u16 = 5;
+/// Set for every promotion, the low two bits pick the piece and
+/// `CAPTURE` marks promotions that capture.
+const PROMOTION: u16 = 8;
+
+impl PackedMove {
+    pub fn from_sq(self) -> Square {
+        Square::from((self.0 & 0x3f) as u64)
+    }
+
+    pub fn to_sq(self) -> Square {
+        Square::from((self.0 >> 6 & 0x3f) as u64)
+    }
+
+    fn flag(self) -> u16 {
+        self.0 >> 12
+    }
+
+    /// Rebuilds the full move, taking the moving piece from `board`. Returns
+    /// `None` if the `from` square is empty.
+    pub fn unpack(self, board: &Board) -> Option<Move> {
+        let from = self.from_sq();
+        let piece = board.piece(from as u64)?;
+        let flag = self.flag();
+
+        let special = if flag & PROMOTION != 0 {
+            Some(SpecialMove::Promotion(match flag & 3 {
+                0 => PieceKind::Horse,
+                1 => PieceKind::Bishop,
+                2 => PieceKind::Rook,
+                _ => PieceKind::Queen,
+            }))
+        } else {
+            match flag {
+                DOUBLE_PAWN_PUSH => Some(SpecialMove::DoublePawnPush),
+                CASTLE => Some(SpecialMove::Castle),
+                EN_PASSANT => Some(SpecialMove::EnPassant),
+                _ => None,
+            }
+        };
+
+        Some(Move {
+            from,
+            to: self.to_sq(),
+            piece,
+            capture: flag & CAPTURE != 0,
+            special,
+        })
+    }
+}
+
+impl From<Move> for PackedMove {
+    fn from(m: Move) -> Self {
+        let capture = if m.capture { CAPTURE } else { QUIET };
+        let flag = match m.special {
+            None => capture,
+            Some(SpecialMove::DoublePawnPush) => DOUBLE_PAWN_PUSH,
+            Some(SpecialMove::Castle) => CASTLE,
+            Some(SpecialMove::EnPassant) => EN_PASSANT,
+            Some(SpecialMove::Promotion(kind)) => {
+                let piece = match kind {
+                    PieceKind::Horse => 0,
+                    PieceKind::Bishop => 1,
+                    PieceKind::Rook => 2,
+                    _ => 3,
+                };
+                PROMOTION | capture | piece
+            }
+        };
+
+        Self(m.from as u16 | (m.to as u16) << 6 | flag << 12)
+    }
+}
+
+impl From<PackedMove> for u16 {
+    fn from(m: PackedMove) -> Self {
+        m.0
+    }
+}
+
+impl From<u16> for PackedMove {
+    fn from(value: u16) -> Self {
+        Self(value)
+    }
+}
+
+#[wasm_bindgen]
+impl Board {
+    /// Inverse of `Move::pack` for a move played from this position.
+    pub fn unpack_move(&self, packed: u16) -> Option<Move> {
+        PackedMove(packed).unpack(self)
+    }
+}
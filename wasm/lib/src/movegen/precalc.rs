@@ -1,35 +1,100 @@
-use super::{magic::*, *};
+#[cfg(not(feature = "hyperbola"))]
+use super::magic::*;
+use super::*;
 use crate::bitboardindex::BitBoardIdx;
-use lazy_static::lazy_static;
+use std::fmt::Display;
+
+/// Attack tables, built by const evaluation so nothing runs at startup.
+pub static PRECALC: Precalc = Precalc::new();
+
+/// Per square entry of the fancy magic tables: the relevant occupancy mask,
+/// its magic number and where the square's slice starts in the shared table.
+#[cfg(not(feature = "hyperbola"))]
+#[derive(Debug, Clone, Copy)]
+pub struct Magic {
+    pub mask: BitBoard,
+    pub magic: u64,
+    pub shift: u32,
+    pub offset: usize,
+}
 
-lazy_static! {
-    pub static ref PRECALC: Precalc = Precalc::default();
+#[cfg(not(feature = "hyperbola"))]
+impl Magic {
+    pub const fn index(&self, occ: BitBoard) -> usize {
+        self.offset + ((occ.0 & self.mask.0).wrapping_mul(self.magic) >> self.shift) as usize
+    }
 }
 
+#[cfg(not(feature = "hyperbola"))]
+pub const BISHOP_MAGICS: [Magic; 64] = calc_magics(&BISHOP_MAGIC, &BISHOP_SHIFT, false);
+#[cfg(not(feature = "hyperbola"))]
+pub const ROOK_MAGICS: [Magic; 64] = calc_magics(&ROOK_MAGIC, &ROOK_SHIFT, true);
+#[cfg(not(feature = "hyperbola"))]
+pub const BISHOP_TABLE_SIZE: usize = table_size(&BISHOP_MAGICS);
+#[cfg(not(feature = "hyperbola"))]
+pub const ROOK_TABLE_SIZE: usize = table_size(&ROOK_MAGICS);
+
 #[derive(Debug, Clone)]
 pub struct Precalc {
-    pub bishop_magic: Box<[[BitBoard; 512]; 64]>,
-    pub rook_magic: Box<[[BitBoard; 4096]; 64]>,
-    pub bishop: Box<[(BitBoard, u32); 64]>,
-    pub rook: Box<[(BitBoard, u32); 64]>,
-    pub pawns: Box<[[BitBoard; 2]; 64]>,
-    pub knight: Box<[BitBoard; 64]>,
-    pub king: Box<[BitBoard; 64]>,
+    #[cfg(not(feature = "hyperbola"))]
+    pub bishop_table: [BitBoard; BISHOP_TABLE_SIZE],
+    #[cfg(not(feature = "hyperbola"))]
+    pub rook_table: [BitBoard; ROOK_TABLE_SIZE],
+    /// Diagonal and anti-diagonal through each square, the square excluded.
+    #[cfg(feature = "hyperbola")]
+    pub bishop_lines: [[u64; 2]; 64],
+    /// File and rank through each square, the square excluded.
+    #[cfg(feature = "hyperbola")]
+    pub rook_lines: [[u64; 2]; 64],
+    pub pawns: [[BitBoard; 2]; 64],
+    pub knight: [BitBoard; 64],
+    pub king: [BitBoard; 64],
+    pub between: [[BitBoard; 64]; 64],
+    pub line: [[BitBoard; 64]; 64],
 }
 
 impl Precalc {
+    pub const fn new() -> Self {
+        Self {
+            #[cfg(not(feature = "hyperbola"))]
+            bishop_table: calc_table(&BISHOP_MAGICS, false),
+            #[cfg(not(feature = "hyperbola"))]
+            rook_table: calc_table(&ROOK_MAGICS, true),
+            #[cfg(feature = "hyperbola")]
+            bishop_lines: calc_lines(false),
+            #[cfg(feature = "hyperbola")]
+            rook_lines: calc_lines(true),
+            pawns: calc_pawns(),
+            knight: calc_knight(),
+            king: calc_king(),
+            between: calc_between(),
+            line: calc_line(),
+        }
+    }
+
+    #[cfg(not(feature = "hyperbola"))]
+    pub fn bishop_attack(&self, square: impl BitBoardIdx, occ: BitBoard) -> BitBoard {
+        self.bishop_table[BISHOP_MAGICS[square.idx_usize()].index(occ)]
+    }
+
+    #[cfg(not(feature = "hyperbola"))]
+    pub fn rook_attack(&self, square: impl BitBoardIdx, occ: BitBoard) -> BitBoard {
+        self.rook_table[ROOK_MAGICS[square.idx_usize()].index(occ)]
+    }
+
+    #[cfg(feature = "hyperbola")]
     pub fn bishop_attack(&self, square: impl BitBoardIdx, occ: BitBoard) -> BitBoard {
-        let occ = occ & self.bishop[square.idx_usize()].0;
-        let occ = occ.0.wrapping_mul(BISHOP_MAGIC[square.idx_usize()]);
-        let occ = occ >> (64 - self.bishop[square.idx_usize()].1);
-        self.bishop_magic[square.idx_usize()][occ as usize]
+        let [diagonal, anti_diagonal] = self.bishop_lines[square.idx_usize()];
+        BitBoard(
+            line_attack(square.idx(), occ.0, diagonal)
+                | line_attack(square.idx(), occ.0, anti_diagonal),
+        )
     }
 
+    #[cfg(feature = "hyperbola")]
     pub fn rook_attack(&self, square: impl BitBoardIdx, occ: BitBoard) -> BitBoard {
-        let occ = occ & self.rook[square.idx_usize()].0;
-        let occ = occ.0.wrapping_mul(ROOK_MAGIC[square.idx_usize()]);
-        let occ = occ >> (64 - self.rook[square.idx_usize()].1);
-        self.rook_magic[square.idx_usize()][occ as usize]
+        let [file, rank] = self.rook_lines[square.idx_usize()];
+        BitBoard(line_attack(square.idx(), occ.0, file) | line_attack(square.idx(), occ.0, rank))
     }
 
     pub fn queen_attack(&self, square: impl BitBoardIdx, occ: BitBoard) -> BitBoard {
@@ -39,193 +104,369 @@ impl Precalc {
 
 impl Default for Precalc {
     fn default() -> Self {
-        Self {
-            bishop_magic: calc_bishop_magic(),
-            rook_magic: calc_rook_magic(),
-            bishop: calc_bishop(),
-            rook: calc_rook(),
-            pawns: calc_pawns(),
-            knight: calc_knight(),
-            king: calc_king(),
-        }
+        Self::new()
     }
 }
 
-pub fn calc_bishop() -> Box<[(BitBoard, u32); 64]> {
-    let mut res = Box::new([(BitBoard(0), 0); 64]);
-    for square in 0..64 {
-        let a = bishop_attack(square);
-        res[square as usize] = (a, a.0.count_ones());
+/// Hyperbola quintessence: the attacks of a slider on `square` along one
+/// line, found by subtracting it from the blockers in both bit orders.
+#[cfg(feature = "hyperbola")]
+fn line_attack(square: u64, occ: u64, line: u64) -> u64 {
+    let o = occ & line;
+    let s = 1u64 << square;
+    let forward = o.wrapping_sub(s.wrapping_mul(2));
+    let reverse = o
+        .reverse_bits()
+        .wrapping_sub(s.reverse_bits().wrapping_mul(2))
+        .reverse_bits();
+    (forward ^ reverse) & line
+}
+
+#[cfg(feature = "hyperbola")]
+const fn calc_lines(rook: bool) -> [[u64; 2]; 64] {
+    let mut res = [[0; 2]; 64];
+    let mut square = 0;
+    while square < 64 {
+        let (rank, file) = (square / 8, square % 8);
+        let mut other = 0;
+        while other < 64 {
+            let (r, f) = (other / 8, other % 8);
+            if other != square {
+                let bit = 1 << other;
+                if rook {
+                    if f == file {
+                        res[square][0] |= bit;
+                    }
+                    if r == rank {
+                        res[square][1] |= bit;
+                    }
+                } else {
+                    if r + file == f + rank {
+                        res[square][0] |= bit;
+                    }
+                    if r + f == rank + file {
+                        res[square][1] |= bit;
+                    }
+                }
+            }
+            other += 1;
+        }
+        square += 1;
     }
     res
 }
 
-pub fn calc_rook() -> Box<[(BitBoard, u32); 64]> {
-    let mut res = Box::new([(BitBoard(0), 0); 64]);
-    for square in 0..64 {
-        let a = rook_attack(square);
-        res[square as usize] = (a, a.0.count_ones());
+#[cfg(not(feature = "hyperbola"))]
+const fn calc_magics(magics: &[u64; 64], shifts: &[u32; 64], rook: bool) -> [Magic; 64] {
+    let mut res = [Magic {
+        mask: BitBoard(0),
+        magic: 0,
+        shift: 0,
+        offset: 0,
+    }; 64];
+    let mut offset = 0;
+    let mut square = 0;
+    while square < 64 {
+        let mask = if rook {
+            rook_attack(square)
+        } else {
+            bishop_attack(square)
+        };
+        let shift = shifts[square as usize];
+        res[square as usize] = Magic {
+            mask,
+            magic: magics[square as usize],
+            shift,
+            offset,
+        };
+        offset += 1 << (64 - shift);
+        square += 1;
     }
     res
 }
 
-pub fn calc_bishop_magic() -> Box<[[BitBoard; 512]; 64]> {
-    let mut res = Box::new([[BitBoard(0); 512]; 64]);
-    for square in 0..64 {
-        let attack_mask = bishop_attack(square);
-        let attack_bits = attack_mask.0.count_ones();
-        let max_idx = 1 << attack_bits;
-
-        for idx in 0..max_idx {
-            let occ = occupancy(idx, attack_mask);
-            let magic_idx = occ.0.wrapping_mul(BISHOP_MAGIC[square as usize]) >> (64 - attack_bits);
-            res[square as usize][magic_idx as usize] = bishop_attack_blocker(square, occ);
+#[cfg(not(feature = "hyperbola"))]
+const fn table_size(magics: &[Magic; 64]) -> usize {
+    magics[63].offset + (1 << (64 - magics[63].shift))
+}
+
+#[cfg(not(feature = "hyperbola"))]
+const fn calc_table<const N: usize>(magics: &[Magic; 64], rook: bool) -> [BitBoard; N] {
+    let mut res = [BitBoard(0); N];
+    let mut square = 0;
+    while square < 64 {
+        let magic = &magics[square as usize];
+        let mask = magic.mask.0;
+
+        // Walks every subset of the mask with the carry-rippler trick.
+        let mut occ = 0u64;
+        loop {
+            res[magic.index(BitBoard(occ))] = if rook {
+                rook_attack_blocker(square, BitBoard(occ))
+            } else {
+                bishop_attack_blocker(square, BitBoard(occ))
+            };
+            occ = occ.wrapping_sub(mask) & mask;
+            if occ == 0 {
+                break;
+            }
         }
+        square += 1;
     }
     res
 }
 
-pub fn calc_rook_magic() -> Box<[[BitBoard; 4096]; 64]> {
-    let mut res = Box::new([[BitBoard(0); 4096]; 64]);
+/// A slider lookup that disagrees with walking the rays.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AttackMismatch {
+    pub piece: PieceKind,
+    pub square: Square,
+    pub occupancy: BitBoard,
+    pub expected: BitBoard,
+    pub found: BitBoard,
+}
+
+impl Display for AttackMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{:?} attacks from {} with occupancy {:#x}: expected {:#x}, found {:#x}",
+            self.piece, self.square, self.occupancy.0, self.expected.0, self.found.0
+        )
+    }
+}
+
+impl std::error::Error for AttackMismatch {}
+
+/// Checks `rook_attack` and `bishop_attack` against the slow ray walking
+/// functions for every blocker subset of every square.
+pub fn verify_attack_tables() -> Result<(), AttackMismatch> {
     for square in 0..64 {
-        let attack_mask = rook_attack(square);
-        let attack_bits = attack_mask.0.count_ones();
-        let max_idx = 1 << attack_bits;
-
-        for idx in 0..max_idx {
-            let occ = occupancy(idx, attack_mask);
-            let magic_idx = occ.0.wrapping_mul(ROOK_MAGIC[square as usize]) >> (64 - attack_bits);
-            res[square as usize][magic_idx as usize] = rook_attack_blocker(square, occ);
+        for piece in [PieceKind::Rook, PieceKind::Bishop] {
+            let mask = match piece {
+                PieceKind::Rook => rook_attack(square),
+                _ => bishop_attack(square),
+            };
+
+            let mut occ = BitBoard(0);
+            loop {
+                let (expected, found) = match piece {
+                    PieceKind::Rook => (
+                        rook_attack_blocker(square, occ),
+                        PRECALC.rook_attack(square, occ),
+                    ),
+                    _ => (
+                        bishop_attack_blocker(square, occ),
+                        PRECALC.bishop_attack(square, occ),
+                    ),
+                };
+                if expected != found {
+                    return Err(AttackMismatch {
+                        piece,
+                        square: Square::from(square),
+                        occupancy: occ,
+                        expected,
+                        found,
+                    });
+                }
+                occ = BitBoard(occ.0.wrapping_sub(mask.0) & mask.0);
+                if occ == 0 {
+                    break;
+                }
+            }
         }
     }
-    res
+    Ok(())
 }
 
-pub fn calc_pawns() -> Box<[[BitBoard; 2]; 64]> {
-    let mut res = Box::new([[BitBoard(0); 2]; 64]);
-    for square in 0..64 {
+#[cfg(debug_assertions)]
+#[wasm_bindgen(js_name = verify_attack_tables)]
+pub fn verify_attack_tables_js() -> Result<(), JsError> {
+    Ok(verify_attack_tables()?)
+}
+
+pub const fn calc_pawns() -> [[BitBoard; 2]; 64] {
+    let mut res = [[BitBoard(0); 2]; 64];
+    let mut square = 0;
+    while square < 64 {
         res[square as usize] = [
             pawn_attack(square, Color::White),
             pawn_attack(square, Color::Black),
         ];
+        square += 1;
     }
     res
 }
 
-pub fn calc_knight() -> Box<[BitBoard; 64]> {
-    let mut res = Box::new([BitBoard(0); 64]);
-    for square in 0..64 {
+pub const fn calc_knight() -> [BitBoard; 64] {
+    let mut res = [BitBoard(0); 64];
+    let mut square = 0;
+    while square < 64 {
         res[square as usize] = knight_attack(square);
+        square += 1;
     }
     res
 }
 
-pub fn calc_king() -> Box<[BitBoard; 64]> {
-    let mut res = Box::new([BitBoard(0); 64]);
-    for square in 0..64 {
+pub const fn calc_king() -> [BitBoard; 64] {
+    let mut res = [BitBoard(0); 64];
+    let mut square = 0;
+    while square < 64 {
         res[square as usize] = king_attack(square);
+        square += 1;
+    }
+    res
+}
+
+/// Squares strictly between two squares on the same rank, file or diagonal.
+pub const fn calc_between() -> [[BitBoard; 64]; 64] {
+    let mut res = [[BitBoard(0); 64]; 64];
+    let mut a = 0;
+    while a < 64 {
+        let mut b = 0;
+        while b < 64 {
+            let (ba, bb) = (BitBoard(1 << a), BitBoard(1 << b));
+            if rook_attack_blocker(a, BitBoard(0)).0 & bb.0 != 0 {
+                res[a as usize][b as usize] =
+                    BitBoard(rook_attack_blocker(a, bb).0 & rook_attack_blocker(b, ba).0);
+            } else if bishop_attack_blocker(a, BitBoard(0)).0 & bb.0 != 0 {
+                res[a as usize][b as usize] =
+                    BitBoard(bishop_attack_blocker(a, bb).0 & bishop_attack_blocker(b, ba).0);
+            }
+            b += 1;
+        }
+        a += 1;
+    }
+    res
+}
+
+/// The whole line through two squares on the same rank, file or diagonal,
+/// edge to edge.
+pub const fn calc_line() -> [[BitBoard; 64]; 64] {
+    let mut res = [[BitBoard(0); 64]; 64];
+    let mut a = 0;
+    while a < 64 {
+        let mut b = 0;
+        while b < 64 {
+            let (ba, bb): (u64, u64) = (1 << a, 1 << b);
+            let (rook_a, rook_b) = (
+                rook_attack_blocker(a, BitBoard(0)).0,
+                rook_attack_blocker(b, BitBoard(0)).0,
+            );
+            let (bishop_a, bishop_b) = (
+                bishop_attack_blocker(a, BitBoard(0)).0,
+                bishop_attack_blocker(b, BitBoard(0)).0,
+            );
+            if rook_a & bb != 0 {
+                res[a as usize][b as usize] = BitBoard((rook_a & rook_b) | ba | bb);
+            } else if bishop_a & bb != 0 {
+                res[a as usize][b as usize] = BitBoard((bishop_a & bishop_b) | ba | bb);
+            }
+            b += 1;
+        }
+        a += 1;
     }
     res
 }
 
-pub fn pawn_attack(square: u64, color: Color) -> BitBoard {
+pub const fn pawn_attack(square: u64, color: Color) -> BitBoard {
     let mut attacks = BitBoard(0);
     match color {
         Color::White => {
-            if (1 << square) & FILE_A == 0 && square <= 56 {
-                attacks.set(square + 7);
+            if (1u64 << square) & FILE_A == 0 && square <= 56 {
+                attacks.0 |= 1 << (square + 7);
             }
-            if (1 << square) & FILE_H == 0 && square <= 54 {
-                attacks.set(square + 9);
+            if (1u64 << square) & FILE_H == 0 && square <= 54 {
+                attacks.0 |= 1 << (square + 9);
             }
         }
         Color::Black => {
-            if (1 << square) & FILE_A == 0 && square >= 9 {
-                attacks.set(square - 9);
+            if (1u64 << square) & FILE_A == 0 && square >= 9 {
+                attacks.0 |= 1 << (square - 9);
             }
-            if (1 << square) & FILE_H == 0 && square >= 7 {
-                attacks.set(square - 7);
+            if (1u64 << square) & FILE_H == 0 && square >= 7 {
+                attacks.0 |= 1 << (square - 7);
             }
         }
     };
     attacks
 }
 
-pub fn knight_attack(square: u64) -> BitBoard {
+pub const fn knight_attack(square: u64) -> BitBoard {
     let mut attacks = BitBoard(0);
-    let sqb = 1 << square;
+    let sqb: u64 = 1 << square;
     if sqb & FILE_A == 0 && sqb & RANK_78 == 0 {
-        attacks.set(square + 15);
+        attacks.0 |= 1 << (square + 15);
     }
     if sqb & FILE_H == 0 && sqb & RANK_78 == 0 {
-        attacks.set(square + 17);
+        attacks.0 |= 1 << (square + 17);
     }
     if sqb & FILE_AB == 0 && sqb & RANK_8 == 0 {
-        attacks.set(square + 6);
+        attacks.0 |= 1 << (square + 6);
     }
     if sqb & FILE_GH == 0 && sqb & RANK_8 == 0 {
-        attacks.set(square + 10);
+        attacks.0 |= 1 << (square + 10);
     }
     if sqb & FILE_H == 0 && sqb & RANK_12 == 0 {
-        attacks.set(square - 15);
+        attacks.0 |= 1 << (square - 15);
     }
     if sqb & FILE_A == 0 && sqb & RANK_12 == 0 {
-        attacks.set(square - 17);
+        attacks.0 |= 1 << (square - 17);
     }
     if sqb & FILE_GH == 0 && sqb & RANK_1 == 0 {
-        attacks.set(square - 6);
+        attacks.0 |= 1 << (square - 6);
     }
     if sqb & FILE_AB == 0 && sqb & RANK_1 == 0 {
-        attacks.set(square - 10);
+        attacks.0 |= 1 << (square - 10);
     }
 
     attacks
 }
 
-pub fn king_attack(square: u64) -> BitBoard {
+pub const fn king_attack(square: u64) -> BitBoard {
     let mut attacks = BitBoard(0);
-    let sqb = 1 << square;
+    let sqb: u64 = 1 << square;
     let file_a = sqb & FILE_A != 0;
     let file_h = sqb & FILE_H != 0;
     let rank_1 = sqb & RANK_1 != 0;
     let rank_8 = sqb & RANK_8 != 0;
 
     if !file_a {
-        attacks.set(square - 1);
+        attacks.0 |= 1 << (square - 1);
         if !rank_1 {
-            attacks.set(square - 9);
+            attacks.0 |= 1 << (square - 9);
         }
         if !rank_8 {
-            attacks.set(square + 7);
+            attacks.0 |= 1 << (square + 7);
         }
     }
     if !file_h {
-        attacks.set(square + 1);
+        attacks.0 |= 1 << (square + 1);
         if !rank_1 {
-            attacks.set(square - 7);
+            attacks.0 |= 1 << (square - 7);
         }
         if !rank_8 {
-            attacks.set(square + 9);
+            attacks.0 |= 1 << (square + 9);
         }
     }
     if !rank_1 {
-        attacks.set(square - 8);
+        attacks.0 |= 1 << (square - 8);
     }
     if !rank_8 {
-        attacks.set(square + 8);
+        attacks.0 |= 1 << (square + 8);
     }
 
     attacks
 }
 
-pub fn bishop_attack(square: u64) -> BitBoard {
+pub const fn bishop_attack(square: u64) -> BitBoard {
     let mut attacks = BitBoard(0);
 
     let (i, j) = (square / 8, square % 8);
     let (mut x, mut y) = (i + 1, j + 1);
     while x < 7 && y < 7 {
         let idx = x * 8 + y;
-        attacks.set(idx);
+        attacks.0 |= 1 << idx;
         x += 1;
         y += 1;
     }
@@ -233,7 +474,7 @@ pub fn bishop_attack(square: u64) -> BitBoard {
         let (mut x, mut y) = (i - 1, j + 1);
         while x > 0 && y < 7 {
             let idx = x * 8 + y;
-            attacks.set(idx);
+            attacks.0 |= 1 << idx;
             x -= 1;
             y += 1;
         }
@@ -242,7 +483,7 @@ pub fn bishop_attack(square: u64) -> BitBoard {
         let (mut x, mut y) = (i + 1, j - 1);
         while x < 7 && y > 0 {
             let idx = x * 8 + y;
-            attacks.set(idx);
+            attacks.0 |= 1 << idx;
             x += 1;
             y -= 1;
         }
@@ -251,7 +492,7 @@ pub fn bishop_attack(square: u64) -> BitBoard {
         let (mut x, mut y) = (i - 1, j - 1);
         while x > 0 && y > 0 {
             let idx = x * 8 + y;
-            attacks.set(idx);
+            attacks.0 |= 1 << idx;
             x -= 1;
             y -= 1;
         }
@@ -260,7 +501,7 @@ pub fn bishop_attack(square: u64) -> BitBoard {
     attacks
 }
 
-pub fn rook_attack(square: u64) -> BitBoard {
+pub const fn rook_attack(square: u64) -> BitBoard {
     let mut attacks = BitBoard(0);
 
     let (i, j) = (square / 8, square % 8);
@@ -268,7 +509,7 @@ pub fn rook_attack(square: u64) -> BitBoard {
     while y < 7 {
         let idx = x * 8 + y;
         if y != j {
-            attacks.set(idx);
+            attacks.0 |= 1 << idx;
         }
         y += 1;
     }
@@ -276,7 +517,7 @@ pub fn rook_attack(square: u64) -> BitBoard {
     while x < 7 {
         let idx = x * 8 + y;
         if x != i {
-            attacks.set(idx);
+            attacks.0 |= 1 << idx;
         }
         x += 1;
     }
@@ -284,15 +525,15 @@ pub fn rook_attack(square: u64) -> BitBoard {
     attacks
 }
 
-pub fn bishop_attack_blocker(square: u64, blockers: BitBoard) -> BitBoard {
+pub const fn bishop_attack_blocker(square: u64, blockers: BitBoard) -> BitBoard {
     let mut attacks = BitBoard(0);
 
     let (i, j) = (square / 8, square % 8);
     let (mut x, mut y) = (i + 1, j + 1);
     while x <= 7 && y <= 7 {
         let idx = x * 8 + y;
-        attacks.set(idx);
-        if blockers.get(idx) {
+        attacks.0 |= 1 << idx;
+        if blockers.0 & (1 << idx) != 0 {
             break;
         }
         x += 1;
@@ -302,8 +543,8 @@ pub fn bishop_attack_blocker(square: u64, blockers: BitBoard) -> BitBoard {
         let (mut x, mut y) = (i - 1, j + 1);
         while y <= 7 {
             let idx = x * 8 + y;
-            attacks.set(idx);
-            if blockers.get(idx) {
+            attacks.0 |= 1 << idx;
+            if blockers.0 & (1 << idx) != 0 {
                 break;
             }
 
@@ -318,8 +559,8 @@ pub fn bishop_attack_blocker(square: u64, blockers: BitBoard) -> BitBoard {
         let (mut x, mut y) = (i + 1, j - 1);
         while x <= 7 {
             let idx = x * 8 + y;
-            attacks.set(idx);
-            if blockers.get(idx) {
+            attacks.0 |= 1 << idx;
+            if blockers.0 & (1 << idx) != 0 {
                 break;
             }
 
@@ -334,8 +575,8 @@ pub fn bishop_attack_blocker(square: u64, blockers: BitBoard) -> BitBoard {
         let (mut x, mut y) = (i - 1, j - 1);
         loop {
             let idx = x * 8 + y;
-            attacks.set(idx);
-            if blockers.get(idx) {
+            attacks.0 |= 1 << idx;
+            if blockers.0 & (1 << idx) != 0 {
                 break;
             }
 
@@ -350,15 +591,15 @@ pub fn bishop_attack_blocker(square: u64, blockers: BitBoard) -> BitBoard {
     attacks
 }
 
-pub fn rook_attack_blocker(square: u64, blockers: BitBoard) -> BitBoard {
+pub const fn rook_attack_blocker(square: u64, blockers: BitBoard) -> BitBoard {
     let mut attacks = BitBoard(0);
 
     let (i, j) = (square / 8, square % 8);
     let (mut x, y) = (i + 1, j);
     while x <= 7 {
         let idx = x * 8 + y;
-        attacks.set(idx);
-        if blockers.get(idx) {
+        attacks.0 |= 1 << idx;
+        if blockers.0 & (1 << idx) != 0 {
             break;
         }
         x += 1;
@@ -367,8 +608,8 @@ pub fn rook_attack_blocker(square: u64, blockers: BitBoard) -> BitBoard {
         let (mut x, y) = (i - 1, j);
         loop {
             let idx = x * 8 + y;
-            attacks.set(idx);
-            if blockers.get(idx) {
+            attacks.0 |= 1 << idx;
+            if blockers.0 & (1 << idx) != 0 {
                 break;
             }
 
@@ -381,8 +622,8 @@ pub fn rook_attack_blocker(square: u64, blockers: BitBoard) -> BitBoard {
     let (x, mut y) = (i, j + 1);
     while y <= 7 {
         let idx = x * 8 + y;
-        attacks.set(idx);
-        if blockers.get(idx) {
+        attacks.0 |= 1 << idx;
+        if blockers.0 & (1 << idx) != 0 {
             break;
         }
         y += 1;
@@ -391,8 +632,8 @@ pub fn rook_attack_blocker(square: u64, blockers: BitBoard) -> BitBoard {
         let (x, mut y) = (i, j - 1);
         loop {
             let idx = x * 8 + y;
-            attacks.set(idx);
-            if blockers.get(idx) {
+            attacks.0 |= 1 << idx;
+            if blockers.0 & (1 << idx) != 0 {
                 break;
             }
 
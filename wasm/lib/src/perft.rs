@@ -0,0 +1,375 @@
+use crate::{
+    board::Board,
+    fen::FenError,
+    movegen::{generate_moves, square_attacked, Move, MoveMasks, SpecialMove},
+};
+use serde::Serialize;
+#[cfg(not(target_arch = "wasm32"))]
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::{fmt::Display, ops::AddAssign};
+use wasm_bindgen::prelude::*;
+
+/// Counts the leaf nodes of the legal move tree `depth` plies deep.
+pub fn perft(board: &mut Board, depth: u32) -> u64 {
+    if depth == 0 {
+        return 1;
+    }
+
+    let moves = generate_moves(board);
+    if depth == 1 {
+        return moves.len() as u64;
+    }
+
+    let mut count = 0;
+    for &m in moves.iter() {
+        let undo = board.make_move_unchecked(m);
+        count += perft(board, depth - 1);
+        board.unmake_move(m, undo);
+    }
+    count
+}
+
+/// Perft split by root move, for comparing against another engine. Empty at
+/// depth 0, where there is no root move to split by.
+pub fn divide(board: &mut Board, depth: u32) -> Vec<(Move, u64)> {
+    if depth == 0 {
+        return Vec::new();
+    }
+
+    generate_moves(board)
+        .into_iter()
+        .map(|m| {
+            let undo = board.make_move_unchecked(m);
+            let count = perft(board, depth - 1);
+            board.unmake_move(m, undo);
+            (m, count)
+        })
+        .collect()
+}
+
+/// `divide` as handed to JS, with moves in the same notation as the native
+/// `perft --divide` output.
+#[derive(Debug, Clone, Serialize, tsify::Tsify)]
+#[tsify(into_wasm_abi)]
+pub struct DivideReport {
+    pub depth: u32,
+    pub nodes: u64,
+    pub moves: Vec<DivideEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, tsify::Tsify)]
+pub struct DivideEntry {
+    #[serde(rename = "move")]
+    pub uci: String,
+    pub nodes: u64,
+}
+
+#[wasm_bindgen(js_name = perft)]
+pub fn perft_js(board: &Board, depth: u32) -> u64 {
+    perft(&mut board.clone(), depth)
+}
+
+/// Returned by the JS `divide` at depth 0, where the single node perft counts
+/// cannot be put under any root move.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ZeroDepthDivide;
+
+impl Display for ZeroDepthDivide {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "divide needs a depth of at least 1")
+    }
+}
+
+impl std::error::Error for ZeroDepthDivide {}
+
+#[wasm_bindgen(js_name = divide)]
+pub fn divide_js(board: &Board, depth: u32) -> Result<DivideReport, JsError> {
+    if depth == 0 {
+        return Err(ZeroDepthDivide.into());
+    }
+    let moves: Vec<_> = divide(&mut board.clone(), depth)
+        .into_iter()
+        .map(|(m, nodes)| DivideEntry {
+            uci: m.to_string(),
+            nodes,
+        })
+        .collect();
+    Ok(DivideReport {
+        depth,
+        nodes: moves.iter().map(|e| e.nodes).sum(),
+        moves,
+    })
+}
+
+/// Subtree counts keyed by Zobrist hash and depth, shared between threads
+/// without locks: each slot stores the key xor-ed with the count, so a torn
+/// write from two threads fails the check instead of returning garbage.
+#[cfg(not(target_arch = "wasm32"))]
+pub struct PerftCache {
+    slots: Box<[(AtomicU64, AtomicU64)]>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl PerftCache {
+    pub fn new(size_mb: usize) -> Self {
+        let len = (size_mb * 1024 * 1024 / 16).next_power_of_two().max(1);
+        Self {
+            slots: (0..len)
+                .map(|_| (AtomicU64::new(0), AtomicU64::new(0)))
+                .collect(),
+        }
+    }
+
+    fn key(hash: u64, depth: u32) -> u64 {
+        hash ^ (depth as u64).wrapping_mul(0x9E3779B97F4A7C15)
+    }
+
+    fn probe(&self, hash: u64, depth: u32) -> Option<u64> {
+        let key = Self::key(hash, depth);
+        let (check, count) = &self.slots[key as usize & (self.slots.len() - 1)];
+        let count = count.load(Ordering::Relaxed);
+        (check.load(Ordering::Relaxed) ^ count == key).then_some(count)
+    }
+
+    fn store(&self, hash: u64, depth: u32, count: u64) {
+        let key = Self::key(hash, depth);
+        let slot = &self.slots[key as usize & (self.slots.len() - 1)];
+        slot.0.store(key ^ count, Ordering::Relaxed);
+        slot.1.store(count, Ordering::Relaxed);
+    }
+}
+
+/// `perft` that looks subtrees up in `cache` before walking them.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn perft_hashed(board: &mut Board, depth: u32, cache: &PerftCache) -> u64 {
+    if depth <= 1 {
+        return perft(board, depth);
+    }
+    if let Some(count) = cache.probe(board.hash(), depth) {
+        return count;
+    }
+
+    let mut count = 0;
+    for &m in generate_moves(board).iter() {
+        let undo = board.make_move_unchecked(m);
+        count += perft_hashed(board, depth - 1, cache);
+        board.unmake_move(m, undo);
+    }
+    cache.store(board.hash(), depth, count);
+    count
+}
+
+/// `divide` with the root moves handed out to `threads` worker threads,
+/// each walking its own copy of the board.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn divide_parallel(
+    board: &Board,
+    depth: u32,
+    threads: usize,
+    cache: Option<&PerftCache>,
+) -> Vec<(Move, u64)> {
+    if depth == 0 {
+        return Vec::new();
+    }
+
+    let moves = generate_moves(board);
+    let counts: Vec<AtomicU64> = moves.iter().map(|_| AtomicU64::new(0)).collect();
+    let next = AtomicUsize::new(0);
+
+    std::thread::scope(|scope| {
+        for _ in 0..threads.clamp(1, moves.len().max(1)) {
+            scope.spawn(|| {
+                let mut board = board.clone();
+                loop {
+                    let idx = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(&m) = moves.get(idx) else {
+                        break;
+                    };
+                    let undo = board.make_move_unchecked(m);
+                    let count = match cache {
+                        Some(cache) => perft_hashed(&mut board, depth - 1, cache),
+                        None => perft(&mut board, depth - 1),
+                    };
+                    board.unmake_move(m, undo);
+                    counts[idx].store(count, Ordering::Relaxed);
+                }
+            });
+        }
+    });
+
+    moves
+        .iter()
+        .zip(counts)
+        .map(|(&m, count)| (m, count.into_inner()))
+        .collect()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn perft_parallel(
+    board: &Board,
+    depth: u32,
+    threads: usize,
+    cache: Option<&PerftCache>,
+) -> u64 {
+    if depth == 0 {
+        return 1;
+    }
+    divide_parallel(board, depth, threads, cache)
+        .iter()
+        .map(|(_, count)| count)
+        .sum()
+}
+
+/// Breakdown of the moves played at the last ply, in the same columns as the
+/// usual perft reference tables.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PerftStats {
+    pub nodes: u64,
+    pub captures: u64,
+    pub en_passants: u64,
+    pub castles: u64,
+    pub promotions: u64,
+    pub checks: u64,
+    pub discovered_checks: u64,
+    pub double_checks: u64,
+    pub checkmates: u64,
+}
+
+impl AddAssign for PerftStats {
+    fn add_assign(&mut self, rhs: Self) {
+        self.nodes += rhs.nodes;
+        self.captures += rhs.captures;
+        self.en_passants += rhs.en_passants;
+        self.castles += rhs.castles;
+        self.promotions += rhs.promotions;
+        self.checks += rhs.checks;
+        self.discovered_checks += rhs.discovered_checks;
+        self.double_checks += rhs.double_checks;
+        self.checkmates += rhs.checkmates;
+    }
+}
+
+/// Perft that also classifies every leaf move. Much slower than `perft`, as
+/// each leaf is played to look for checks and mates.
+pub fn perft_stats(board: &mut Board, depth: u32) -> PerftStats {
+    let mut stats = PerftStats::default();
+    if depth == 0 {
+        stats.nodes = 1;
+        return stats;
+    }
+
+    for &m in generate_moves(board).iter() {
+        let undo = board.make_move_unchecked(m);
+        if depth == 1 {
+            stats += leaf_stats(board, m);
+        } else {
+            stats += perft_stats(board, depth - 1);
+        }
+        board.unmake_move(m, undo);
+    }
+    stats
+}
+
+/// Classifies `m`, which has just been played on `board`.
+fn leaf_stats(board: &Board, m: Move) -> PerftStats {
+    let mut stats = PerftStats {
+        nodes: 1,
+        captures: m.capture as u64,
+        en_passants: (m.special == Some(SpecialMove::EnPassant)) as u64,
+        castles: (m.special == Some(SpecialMove::Castle)) as u64,
+        promotions: m.promotion().is_some() as u64,
+        ..Default::default()
+    };
+
+    let king = board.boards(board.side_to_move)[5].0.trailing_zeros() as u64;
+    if !square_attacked(board, king, m.piece.color) {
+        return stats;
+    }
+    stats.checks = 1;
+
+    // Discovered means the piece that moved (the rook when castling) is not
+    // one of the checkers. The reference tables count a double check where
+    // it is as a plain check.
+    let moved_to = if m.special == Some(SpecialMove::Castle) {
+        let (rank, file) = (m.to as u64 / 8, if m.to.file() == 6 { 5 } else { 3 });
+        rank * 8 + file
+    } else {
+        m.to as u64
+    };
+    let checkers = MoveMasks::new(board).checkers;
+    if !checkers.get(moved_to) {
+        stats.discovered_checks = 1;
+    }
+    if checkers.0.count_ones() > 1 {
+        stats.double_checks = 1;
+    }
+    if generate_moves(board).is_empty() {
+        stats.checkmates = 1;
+    }
+    stats
+}
+
+/// One line of a perft EPD suite: a position and its expected node counts,
+/// e.g. `rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - ;D1 20 ;D2 400`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EpdEntry {
+    pub fen: String,
+    pub counts: Vec<(u32, u64)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EpdError {
+    Fen(FenError),
+    InvalidCount(String),
+}
+
+impl Display for EpdError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Fen(e) => write!(f, "{}", e),
+            Self::InvalidCount(s) => write!(f, "invalid depth count '{}'", s),
+        }
+    }
+}
+
+impl std::error::Error for EpdError {}
+
+impl From<FenError> for EpdError {
+    fn from(e: FenError) -> Self {
+        Self::Fen(e)
+    }
+}
+
+impl EpdEntry {
+    pub fn parse(line: &str) -> Result<Self, EpdError> {
+        let mut fields = line.split(';');
+        let fen = fields.next().unwrap_or_default().trim().to_string();
+        Board::try_from_fen_strict(&fen)?;
+
+        let counts = fields
+            .map(|field| {
+                let field = field.trim();
+                let invalid = || EpdError::InvalidCount(field.to_string());
+                let (depth, count) = field.split_once(char::is_whitespace).ok_or_else(invalid)?;
+                let depth = depth
+                    .strip_prefix('D')
+                    .and_then(|d| d.parse().ok())
+                    .ok_or_else(invalid)?;
+                let count = count.trim().parse().map_err(|_| invalid())?;
+                Ok((depth, count))
+            })
+            .collect::<Result<_, EpdError>>()?;
+
+        Ok(Self { fen, counts })
+    }
+
+    /// Parses every non-empty line that is not a `#` comment.
+    pub fn parse_suite(suite: &str) -> Result<Vec<Self>, EpdError> {
+        suite
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(Self::parse)
+            .collect()
+    }
+}
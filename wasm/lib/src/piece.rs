@@ -13,6 +13,15 @@ pub enum PieceKind {
 }
 
 impl PieceKind {
+    pub const ALL: [PieceKind; 6] = [
+        Self::Pawn,
+        Self::Horse,
+        Self::Bishop,
+        Self::Rook,
+        Self::Queen,
+        Self::King,
+    ];
+
     pub fn letter(&self) -> &'static str {
         match self {
             Self::Pawn => "p",
@@ -31,7 +31,7 @@ impl Square {
 #[wasm_bindgen]
 pub fn square_from_num(value: u32) -> Square {
     match value {
-        0..=63 => unsafe { std::mem::transmute(value as u8) },
+        0..=63 => unsafe { std::mem::transmute::<u8, Square>(value as u8) },
         _ => panic!("Invalid square index"),
     }
 }
@@ -44,7 +44,7 @@ pub fn square_to_num(s: Square) -> u32 {
 impl From<u64> for Square {
     fn from(value: u64) -> Self {
         match value {
-            0..=63 => unsafe { std::mem::transmute(value as u8) },
+            0..=63 => unsafe { std::mem::transmute::<u8, Square>(value as u8) },
             _ => panic!("Invalid square index"),
         }
     }
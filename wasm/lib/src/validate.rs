@@ -0,0 +1,164 @@
+use crate::{
+    bitboard::*,
+    board::{Board, Castle},
+    fen::FenError,
+    movegen::square_attacked,
+    piece::{Color, Piece, PieceKind},
+    square::Square,
+};
+use std::fmt::Display;
+use wasm_bindgen::prelude::*;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PositionError {
+    KingCount { color: Color, count: u32 },
+    PawnOnBackRank(Square),
+    OpponentInCheck,
+    CastlingWithoutKing(char),
+    CastlingWithoutRook(char),
+    EnPassantWrongRank(Square),
+    EnPassantOccupied(Square),
+    EnPassantWithoutPawn(Square),
+}
+
+impl Display for PositionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::KingCount { color, count } => {
+                write!(f, "{:?} has {} kings, expected 1", color, count)
+            }
+            Self::PawnOnBackRank(sq) => write!(f, "pawn on back rank square {}", sq),
+            Self::OpponentInCheck => write!(f, "side not to move is in check"),
+            Self::CastlingWithoutKing(c) => {
+                write!(f, "castling right '{}' without king on its square", c)
+            }
+            Self::CastlingWithoutRook(c) => {
+                write!(f, "castling right '{}' without rook on its corner", c)
+            }
+            Self::EnPassantWrongRank(sq) => {
+                write!(f, "en passant square {} is on the wrong rank", sq)
+            }
+            Self::EnPassantOccupied(sq) => {
+                write!(
+                    f,
+                    "en passant square {} or the square behind it is occupied",
+                    sq
+                )
+            }
+            Self::EnPassantWithoutPawn(sq) => {
+                write!(f, "en passant square {} has no double pushed pawn", sq)
+            }
+        }
+    }
+}
+
+impl Board {
+    pub fn validate(&self) -> Vec<PositionError> {
+        let mut errors = Vec::new();
+
+        let w_kings = self.w_king.0.count_ones();
+        let b_kings = self.b_king.0.count_ones();
+        if w_kings != 1 {
+            errors.push(PositionError::KingCount {
+                color: Color::White,
+                count: w_kings,
+            });
+        }
+        if b_kings != 1 {
+            errors.push(PositionError::KingCount {
+                color: Color::Black,
+                count: b_kings,
+            });
+        }
+
+        let mut back_rank_pawns = (self.w_pawn | self.b_pawn) & (RANK_1 | RANK_8);
+        while back_rank_pawns != 0 {
+            let sq = Square::from(back_rank_pawns.0.trailing_zeros() as u64);
+            errors.push(PositionError::PawnOnBackRank(sq));
+            back_rank_pawns &= back_rank_pawns - 1;
+        }
+
+        if w_kings == 1 && b_kings == 1 {
+            let (king, attacker) = match self.side_to_move {
+                Color::White => (self.b_king, Color::White),
+                Color::Black => (self.w_king, Color::Black),
+            };
+            if square_attacked(self, king.0.trailing_zeros() as u64, attacker) {
+                errors.push(PositionError::OpponentInCheck);
+            }
+        }
+
+        for (castle, c, color, king_sq, rook_sq) in [
+            (Castle::WhiteKing, 'K', Color::White, Square::E1, Square::H1),
+            (
+                Castle::WhiteQueen,
+                'Q',
+                Color::White,
+                Square::E1,
+                Square::A1,
+            ),
+            (Castle::BlackKing, 'k', Color::Black, Square::E8, Square::H8),
+            (
+                Castle::BlackQueen,
+                'q',
+                Color::Black,
+                Square::E8,
+                Square::A8,
+            ),
+        ] {
+            if self.can_castle & castle as u8 == 0 {
+                continue;
+            }
+            if self.piece(king_sq as u64) != Some(Piece::new(PieceKind::King, color)) {
+                errors.push(PositionError::CastlingWithoutKing(c));
+            }
+            if self.piece(rook_sq as u64) != Some(Piece::new(PieceKind::Rook, color)) {
+                errors.push(PositionError::CastlingWithoutRook(c));
+            }
+        }
+
+        if let Some(ep) = self.en_passant {
+            // The pawn that just moved sits one rank past the target square
+            // and came from the square one rank before it.
+            let (rank, pawns, pawn_sq, from_sq) = match self.side_to_move {
+                Color::White => (5, self.b_pawn, ep as u64 - 8, ep as u64 + 8),
+                Color::Black => (2, self.w_pawn, ep as u64 + 8, ep as u64 - 8),
+            };
+            if ep.rank() != rank {
+                errors.push(PositionError::EnPassantWrongRank(ep));
+            } else {
+                if self.occ.get(ep) || self.occ.get(from_sq) {
+                    errors.push(PositionError::EnPassantOccupied(ep));
+                }
+                if !pawns.get(pawn_sq) {
+                    errors.push(PositionError::EnPassantWithoutPawn(ep));
+                }
+            }
+        }
+
+        errors
+    }
+
+    pub fn try_from_fen_strict(fen: &str) -> Result<Board, FenError> {
+        let board = Self::try_from_fen(fen)?;
+        let errors = board.validate();
+        if errors.is_empty() {
+            Ok(board)
+        } else {
+            Err(FenError::IllegalPosition(errors))
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl Board {
+    #[wasm_bindgen(js_name = validate)]
+    pub fn validate_js(&self) -> JsValue {
+        let errors = self
+            .validate()
+            .iter()
+            .map(|e| e.to_string())
+            .collect::<Vec<_>>();
+        serde_wasm_bindgen::to_value(&errors).unwrap()
+    }
+}
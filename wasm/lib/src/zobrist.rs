@@ -0,0 +1,71 @@
+use crate::{
+    bitboardindex::BitBoardIdx,
+    piece::{Color, Piece},
+};
+
+pub static ZOBRIST: Zobrist = Zobrist::new(0x9E3779B97F4A7C15);
+
+#[derive(Debug, Clone)]
+pub struct Zobrist {
+    pub pieces: [[u64; 64]; 12],
+    pub castling: [u64; 16],
+    pub en_passant: [u64; 8],
+    pub side_to_move: u64,
+}
+
+impl Zobrist {
+    const fn new(seed: u64) -> Self {
+        let mut state = seed;
+
+        let mut pieces = [[0; 64]; 12];
+        let mut i = 0;
+        while i < 12 {
+            let mut j = 0;
+            while j < 64 {
+                (pieces[i][j], state) = splitmix64(state);
+                j += 1;
+            }
+            i += 1;
+        }
+
+        let mut castling = [0; 16];
+        let mut i = 0;
+        while i < 16 {
+            (castling[i], state) = splitmix64(state);
+            i += 1;
+        }
+
+        let mut en_passant = [0; 8];
+        let mut i = 0;
+        while i < 8 {
+            (en_passant[i], state) = splitmix64(state);
+            i += 1;
+        }
+
+        let (side_to_move, _) = splitmix64(state);
+
+        Self {
+            pieces,
+            castling,
+            en_passant,
+            side_to_move,
+        }
+    }
+
+    pub fn piece(&self, piece: Piece, square: impl BitBoardIdx) -> u64 {
+        let idx = match piece.color {
+            Color::White => piece.kind as usize,
+            Color::Black => piece.kind as usize + 6,
+        };
+        self.pieces[idx][square.idx_usize()]
+    }
+}
+
+/// One step of splitmix64: the next output and the next state.
+pub const fn splitmix64(state: u64) -> (u64, u64) {
+    let state = state.wrapping_add(0x9E3779B97F4A7C15);
+    let mut z = state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+    (z ^ (z >> 31), state)
+}
@@ -0,0 +1,140 @@
+use lib::{
+    board::{Board, MoveError},
+    movegen::{Move, SpecialMove},
+    piece::{Color, Piece, PieceKind},
+    square::Square,
+};
+
+fn quiet(from: Square, to: Square, kind: PieceKind, color: Color) -> Move {
+    Move::new(from, to, Piece::new(kind, color), false, None)
+}
+
+/// Asserts that `m` is rejected with `err` without touching the board.
+fn assert_rejected_with(fen: &str, m: Move, err: MoveError) {
+    let mut board = Board::from_fen(fen);
+    let before = format!("{:?}", board);
+    assert_eq!(board.make_move(m), Err(err), "{}", m);
+    assert_eq!(format!("{:?}", board), before, "{}", m);
+    assert_eq!(board.to_fen(), fen);
+}
+
+fn assert_rejected(fen: &str, m: Move) {
+    assert_rejected_with(fen, m, MoveError::IllegalMove(m));
+}
+
+#[test]
+fn make_move_rejects_slider_jumps() {
+    let start = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+    assert_rejected(
+        start,
+        quiet(Square::D1, Square::D5, PieceKind::Queen, Color::White),
+    );
+    assert_rejected(
+        start,
+        quiet(Square::C1, Square::H6, PieceKind::Bishop, Color::White),
+    );
+    assert_rejected(
+        start,
+        quiet(Square::A1, Square::A4, PieceKind::Rook, Color::White),
+    );
+}
+
+#[test]
+fn make_move_rejects_unreachable_and_self_check_moves() {
+    // A knight cannot move like a king, and the pinned knight cannot move.
+    assert_rejected(
+        "4k3/8/8/8/8/8/8/1N2K3 w - - 0 1",
+        quiet(Square::B1, Square::B2, PieceKind::Horse, Color::White),
+    );
+    assert_rejected(
+        "4r1k1/8/8/8/8/8/4N3/4K3 w - - 0 1",
+        quiet(Square::E2, Square::C3, PieceKind::Horse, Color::White),
+    );
+    // Castling through an attacked square.
+    assert_rejected(
+        "4kr2/8/8/8/8/8/8/4K2R w K - 0 1",
+        Move::new(
+            Square::E1,
+            Square::G1,
+            Piece::new(PieceKind::King, Color::White),
+            false,
+            Some(SpecialMove::Castle),
+        ),
+    );
+}
+
+#[test]
+fn make_move_plays_legal_moves() {
+    let mut board = Board::start_pos();
+    let m = Move::new(
+        Square::E2,
+        Square::E4,
+        Piece::new(PieceKind::Pawn, Color::White),
+        false,
+        Some(SpecialMove::DoublePawnPush),
+    );
+    board.make_move(m).unwrap();
+    assert_eq!(
+        board.to_fen(),
+        "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1"
+    );
+}
+
+#[test]
+fn make_move_rejects_forged_moves() {
+    let start = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+    assert_rejected_with(
+        start,
+        quiet(Square::E7, Square::E6, PieceKind::Pawn, Color::Black),
+        MoveError::WrongSide(Color::Black),
+    );
+    assert_rejected_with(
+        start,
+        quiet(Square::E1, Square::E3, PieceKind::Queen, Color::White),
+        MoveError::PieceMismatch {
+            square: Square::E1,
+            expected: Piece::new(PieceKind::Queen, Color::White),
+            found: Some(Piece::new(PieceKind::King, Color::White)),
+        },
+    );
+    // `capture: false` onto an occupied square.
+    assert_rejected_with(
+        "4k3/8/8/8/8/8/3p4/4K3 w - - 0 1",
+        quiet(Square::E1, Square::D2, PieceKind::King, Color::White),
+        MoveError::CaptureMismatch(Square::D2),
+    );
+}
+
+#[test]
+fn play_looks_up_the_legal_move() {
+    let mut board = Board::start_pos();
+    assert_eq!(
+        board.play(Square::E2, Square::E5, None),
+        Err(MoveError::NoLegalMove {
+            from: Square::E2,
+            to: Square::E5,
+        })
+    );
+    assert_eq!(board.to_fen(), Board::start_pos().to_fen());
+
+    let m = board.play(Square::G1, Square::F3, None).unwrap();
+    assert_eq!(m.piece, Piece::new(PieceKind::Horse, Color::White));
+    assert!(!m.capture);
+}
+
+#[test]
+fn play_requires_a_promotion_piece() {
+    let fen = "4k3/P7/8/8/8/8/8/4K3 w - - 0 1";
+    let mut board = Board::from_fen(fen);
+    assert_eq!(
+        board.play(Square::A7, Square::A8, None),
+        Err(MoveError::MissingPromotion(Square::A8))
+    );
+    assert_eq!(board.to_fen(), fen);
+
+    let m = board
+        .play(Square::A7, Square::A8, Some(PieceKind::Horse))
+        .unwrap();
+    assert_eq!(m.promotion(), Some(PieceKind::Horse));
+    assert_eq!(board.to_fen(), "N3k3/8/8/8/8/8/8/4K3 b - - 0 1");
+}
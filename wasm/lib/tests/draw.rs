@@ -0,0 +1,218 @@
+use lib::{
+    board::{Board, DrawReason, GameState, MoveError},
+    movegen::generate_moves,
+    square::Square,
+};
+
+fn state(fen: &str) -> (GameState, Option<DrawReason>, Option<DrawReason>) {
+    let mut board = Board::from_fen(fen);
+    board.update_state();
+    (board.game_state, board.draw_reason, board.claimable_draw)
+}
+
+#[test]
+fn fifty_and_seventy_five_move_rules() {
+    assert_eq!(
+        state("4k3/8/8/8/8/8/8/R3K3 w - - 99 80"),
+        (GameState::InProgress, None, None)
+    );
+    assert_eq!(
+        state("4k3/8/8/8/8/8/8/R3K3 w - - 100 80"),
+        (GameState::InProgress, None, Some(DrawReason::FiftyMoveRule))
+    );
+    assert_eq!(
+        state("4k3/8/8/8/8/8/8/R3K3 w - - 150 80"),
+        (GameState::Draw, Some(DrawReason::SeventyFiveMoveRule), None)
+    );
+}
+
+#[test]
+fn halfmove_clock_counts_and_resets() {
+    let mut board = Board::from_fen("4k3/8/8/8/8/8/4P3/R3K3 w - - 149 80");
+    board.play(Square::A1, Square::A2, None).unwrap();
+    board.update_state();
+    assert_eq!(board.halfmove_clock, 150);
+    assert_eq!(board.draw_reason, Some(DrawReason::SeventyFiveMoveRule));
+
+    let mut board = Board::from_fen("4k3/8/8/8/8/8/4P3/R3K3 w - - 149 80");
+    board.play(Square::E2, Square::E4, None).unwrap();
+    board.update_state();
+    assert_eq!(board.halfmove_clock, 0);
+    assert_eq!(board.game_state, GameState::InProgress);
+}
+
+#[test]
+fn claimed_draw_stays_claimed() {
+    let mut board = Board::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 100 80");
+    board.update_state();
+    assert!(board.claim_draw());
+    board.update_state();
+    assert_eq!(board.game_state, GameState::Draw);
+    assert_eq!(board.draw_reason, Some(DrawReason::FiftyMoveRule));
+    assert!(!board.claim_draw());
+}
+
+#[test]
+fn claim_blocks_moves_until_taken_back() {
+    let mut board = Board::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 99 80");
+    let m = generate_moves(&board)[0];
+    let undo = board.make_move(m).unwrap();
+    board.update_state();
+    assert!(board.claim_draw());
+
+    let reply = generate_moves(&board)[0];
+    assert_eq!(board.make_move(reply), Err(MoveError::DrawClaimed));
+    assert_eq!(
+        board.play(reply.from, reply.to, None),
+        Err(MoveError::DrawClaimed)
+    );
+
+    board.unmake_move(m, undo);
+    board.update_state();
+    assert_eq!(board.game_state, GameState::InProgress);
+    assert_eq!(board.draw_reason, None);
+    assert!(board.make_move(m).is_ok());
+}
+
+#[test]
+fn nothing_to_claim() {
+    let mut board = Board::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 99 80");
+    board.update_state();
+    assert!(!board.claim_draw());
+    assert_eq!(board.game_state, GameState::InProgress);
+}
+
+/// Plays `moves` as (from, to) pairs, updating the game state after each.
+fn play(board: &mut Board, moves: &[(Square, Square)]) {
+    for &(from, to) in moves {
+        board.play(from, to, None).unwrap();
+        board.update_state();
+    }
+}
+
+const KNIGHT_SHUFFLE: [(Square, Square); 4] = [
+    (Square::G1, Square::F3),
+    (Square::G8, Square::F6),
+    (Square::F3, Square::G1),
+    (Square::F6, Square::G8),
+];
+
+#[test]
+fn threefold_and_fivefold_repetition() {
+    let mut board = Board::start_pos();
+    play(&mut board, &KNIGHT_SHUFFLE);
+    assert_eq!(board.repetitions(), 2);
+    assert_eq!(board.claimable_draw, None);
+
+    play(&mut board, &KNIGHT_SHUFFLE);
+    assert_eq!(board.repetitions(), 3);
+    assert_eq!(board.game_state, GameState::InProgress);
+    assert_eq!(board.claimable_draw, Some(DrawReason::ThreefoldRepetition));
+
+    play(&mut board, &KNIGHT_SHUFFLE);
+    assert_eq!(board.repetitions(), 4);
+    assert_eq!(board.game_state, GameState::InProgress);
+
+    play(&mut board, &KNIGHT_SHUFFLE);
+    assert_eq!(board.repetitions(), 5);
+    assert_eq!(board.game_state, GameState::Draw);
+    assert_eq!(board.draw_reason, Some(DrawReason::FivefoldRepetition));
+}
+
+#[test]
+fn pinned_en_passant_pawn_does_not_break_repetition() {
+    // The b5 pawn cannot take on c6 without exposing its king to the rook.
+    let pinned = Board::from_fen("8/8/8/KPp4r/8/8/8/4k3 w - c6 0 1");
+    assert_eq!(
+        pinned.hash(),
+        Board::from_fen("8/8/8/KPp4r/8/8/8/4k3 w - - 0 1").hash()
+    );
+
+    let mut board = Board::from_fen("8/2p5/8/KP5r/8/8/8/4k3 b - - 0 1");
+    play(
+        &mut board,
+        &[
+            (Square::C7, Square::C5),
+            (Square::A5, Square::A6),
+            (Square::E1, Square::E2),
+            (Square::A6, Square::A5),
+            (Square::E2, Square::E1),
+        ],
+    );
+    assert_eq!(board.repetitions(), 2);
+
+    // Unpinned, the capture is possible and the positions differ.
+    let free = Board::from_fen("8/8/8/1Pp4r/K7/8/8/4k3 w - c6 0 1");
+    assert_ne!(
+        free.hash(),
+        Board::from_fen("8/8/8/1Pp4r/K7/8/8/4k3 w - - 0 1").hash()
+    );
+}
+
+#[test]
+fn lost_castling_rights_break_repetition() {
+    let mut board = Board::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1");
+    let king_shuffle = [
+        (Square::E1, Square::D1),
+        (Square::E8, Square::D8),
+        (Square::D1, Square::E1),
+        (Square::D8, Square::E8),
+    ];
+    play(&mut board, &king_shuffle);
+    assert_eq!(board.repetitions(), 1);
+    play(&mut board, &king_shuffle);
+    assert_eq!(board.repetitions(), 2);
+}
+
+#[test]
+fn insufficient_material() {
+    for (fen, dead) in [
+        ("4k3/8/8/8/8/8/8/4K3 w - - 0 1", true),
+        ("4k3/8/8/8/8/8/8/2B1K3 w - - 0 1", true),
+        ("4k3/8/8/8/8/8/8/1N2K3 b - - 0 1", true),
+        // Both bishops on dark squares, then on opposite colors.
+        ("4kb2/8/8/8/8/8/8/2B1K3 w - - 0 1", true),
+        ("2b1k3/8/8/8/8/8/8/2B1K3 w - - 0 1", false),
+        ("4k3/8/8/8/8/8/8/1NN1K3 w - - 0 1", false),
+        ("4k3/8/8/8/8/8/4P3/4K3 w - - 0 1", false),
+        ("4k3/8/8/8/8/8/8/R3K3 w - - 0 1", false),
+    ] {
+        let expected = if dead {
+            (
+                GameState::Draw,
+                Some(DrawReason::InsufficientMaterial),
+                None,
+            )
+        } else {
+            (GameState::InProgress, None, None)
+        };
+        assert_eq!(state(fen), expected, "{}", fen);
+    }
+}
+
+#[test]
+fn locked_pawn_chain() {
+    assert_eq!(
+        state("4k3/8/8/1p1p1p1p/1P1P1P1P/8/8/4K3 w - - 0 1"),
+        (GameState::Draw, Some(DrawReason::DeadPosition), None)
+    );
+    // With the a-c files open the white king can walk up to d5.
+    assert_eq!(
+        state("4k3/8/8/3p1p1p/3P1P1P/8/8/4K3 w - - 0 1"),
+        (GameState::InProgress, None, None)
+    );
+    assert_eq!(
+        state("4k3/8/8/1p1p1p1p/1PpP1P1P/2P5/8/4K3 b - - 0 1"),
+        (GameState::Draw, Some(DrawReason::DeadPosition), None)
+    );
+    // The en passant capture on b3 opens the b-file.
+    assert_eq!(
+        state("4k3/8/8/1p1p1p1p/1PpP1P1P/2P5/8/4K3 b - b3 0 1"),
+        (GameState::InProgress, None, None)
+    );
+    // Pawns that can still capture are not locked.
+    assert_eq!(
+        state("4k3/8/8/3pp3/3PP3/8/8/4K3 w - - 0 1"),
+        (GameState::InProgress, None, None)
+    );
+}
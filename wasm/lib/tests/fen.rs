@@ -0,0 +1,86 @@
+use lib::{
+    board::{Board, DEFAULT_FEN},
+    fen::{FenError, FenField},
+    square::Square,
+};
+
+#[test]
+fn to_fen_round_trip() {
+    for fen in [
+        DEFAULT_FEN,
+        "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1",
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+        "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10",
+        "4k3/8/8/8/8/8/8/R3K3 b Qk - 37 112",
+    ] {
+        assert_eq!(Board::from_fen(fen).to_fen(), fen);
+    }
+}
+
+#[test]
+fn try_from_fen_errors() {
+    for (fen, expected) in [
+        ("", FenError::MissingField(FenField::PiecePlacement)),
+        (
+            "8/8/8/8/8/8/8/8",
+            FenError::MissingField(FenField::SideToMove),
+        ),
+        (
+            "8/8/8/8/8/8/8/8 w",
+            FenError::MissingField(FenField::Castling),
+        ),
+        (
+            "8/8/8/8/8/8/8/8 w -",
+            FenError::MissingField(FenField::EnPassant),
+        ),
+        ("8/8/8/8/8/8/8 w - -", FenError::RankCount(7)),
+        (
+            "8/8/8/8/8/8/8/7 w - -",
+            FenError::RankLength { rank: 1, len: 7 },
+        ),
+        (
+            "9/8/8/8/8/8/8/8 w - -",
+            FenError::InvalidPiece {
+                square: Square::A8,
+                c: '9',
+            },
+        ),
+        (
+            "8/8/8/8/8/8/8/ppppppppp w - -",
+            FenError::RankLength { rank: 1, len: 9 },
+        ),
+        (
+            "8/8/8/8/4x3/8/8/8 w - -",
+            FenError::InvalidPiece {
+                square: Square::E4,
+                c: 'x',
+            },
+        ),
+        (
+            "8/8/8/8/8/8/8/8 white - -",
+            FenError::InvalidSideToMove("white".to_string()),
+        ),
+        ("8/8/8/8/8/8/8/8 w KX -", FenError::InvalidCastling('X')),
+        ("8/8/8/8/8/8/8/8 w KKQQ -", FenError::InvalidCastling('K')),
+        (
+            "8/8/8/8/8/8/8/8 w - e4",
+            FenError::InvalidEnPassant("e4".to_string()),
+        ),
+        (
+            "8/8/8/8/8/8/8/8 w - - x 1",
+            FenError::InvalidHalfmoveClock("x".to_string()),
+        ),
+        (
+            "8/8/8/8/8/8/8/8 w - - 0 0",
+            FenError::InvalidFullmoveNumber("0".to_string()),
+        ),
+        (
+            "8/8/8/8/8/8/8/8 w - - 0 1 extra junk",
+            FenError::TrailingField("extra".to_string()),
+        ),
+    ] {
+        assert_eq!(Board::try_from_fen(fen).unwrap_err(), expected, "{}", fen);
+    }
+}
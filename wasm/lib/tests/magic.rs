@@ -0,0 +1,8 @@
+use lib::movegen::precalc::verify_attack_tables;
+
+#[test]
+fn slider_tables_match_ray_walks() {
+    if let Err(err) = verify_attack_tables() {
+        panic!("{}", err);
+    }
+}
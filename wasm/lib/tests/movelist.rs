@@ -0,0 +1,84 @@
+use lib::{
+    movegen::{
+        movelist::{MoveList, MAX_MOVES},
+        Move,
+    },
+    piece::{Color, Piece, PieceKind},
+    square::Square,
+};
+
+fn knight_move(to: u64) -> Move {
+    Move::new(
+        Square::A1,
+        Square::from(to),
+        Piece::new(PieceKind::Horse, Color::White),
+        false,
+        None,
+    )
+}
+
+fn filled(len: u64) -> MoveList {
+    let mut list = MoveList::new();
+    for to in 0..len {
+        list.push(knight_move(to % 64));
+    }
+    list
+}
+
+#[test]
+fn push_len_iter_contains() {
+    let list = filled(3);
+    assert_eq!(list.len(), 3);
+    assert!(!list.is_empty());
+    assert_eq!(list[1], knight_move(1));
+    assert!(list.contains(&knight_move(2)));
+    assert!(!list.contains(&knight_move(3)));
+    assert_eq!(
+        list.iter().map(|m| m.to).collect::<Vec<_>>(),
+        [Square::A1, Square::B1, Square::C1]
+    );
+    assert_eq!(list.clone().into_iter().len(), 3);
+    assert_eq!(Box::<[Move]>::from(list).len(), 3);
+
+    assert!(MoveList::new().is_empty());
+    assert_eq!(MoveList::default().iter().count(), 0);
+}
+
+#[test]
+fn sort_by_score_puts_best_first() {
+    let mut list = filled(5);
+    list.sort_by_score(|m| m.to as u64 % 3);
+    let scores: Vec<_> = list.iter().map(|m| m.to as u64 % 3).collect();
+    assert_eq!(scores, [2, 1, 1, 0, 0]);
+}
+
+#[test]
+fn fills_to_capacity() {
+    let list = filled(MAX_MOVES as u64);
+    assert_eq!(list.len(), MAX_MOVES);
+    assert_eq!(
+        list[MAX_MOVES - 1],
+        knight_move((MAX_MOVES as u64 - 1) % 64)
+    );
+}
+
+#[test]
+#[should_panic]
+fn push_past_capacity_panics() {
+    let mut list = filled(MAX_MOVES as u64);
+    list.push(knight_move(0));
+}
+
+/// Only the pushed moves are ever read, so lists going out of scope half
+/// filled or half consumed never touch the uninitialized tail.
+#[test]
+fn drop_partially_filled_or_consumed() {
+    {
+        let list = filled(7);
+        assert_eq!(list.len(), 7);
+    }
+
+    let mut iter = filled(7).into_iter();
+    assert_eq!(iter.next(), Some(knight_move(0)));
+    assert_eq!(iter.len(), 6);
+}
@@ -0,0 +1,296 @@
+use lib::{
+    board::Board,
+    fen::FenError,
+    movegen::{
+        generate_moves, generate_moves_mode, packed::PackedMove, GenMode, MoveMasks, SpecialMove,
+    },
+    perft::{perft, perft_parallel, perft_stats, EpdEntry, EpdError, PerftCache, PerftStats},
+};
+
+const SUITE: &str = include_str!("../perft.epd");
+
+/// Depths whose expected count stays under this run by default, the rest
+/// only with `cargo test --release -- --ignored`.
+const FAST_NODES: u64 = 1_000_000;
+
+fn entry(fen_prefix: &str) -> EpdEntry {
+    EpdEntry::parse_suite(SUITE)
+        .unwrap()
+        .into_iter()
+        .find(|e| e.fen.starts_with(fen_prefix))
+        .unwrap()
+}
+
+fn check(fen_prefix: &str, deep: bool) {
+    let entry = entry(fen_prefix);
+    let mut board = Board::from_fen(&entry.fen);
+    for &(depth, expected) in &entry.counts {
+        if (expected <= FAST_NODES) != deep {
+            assert_eq!(
+                perft(&mut board, depth),
+                expected,
+                "{} depth {}",
+                entry.fen,
+                depth
+            );
+        }
+    }
+}
+
+const START: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w";
+const KIWIPETE: &str = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w";
+const POSITION_3: &str = "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w";
+const POSITION_4: &str = "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w";
+const POSITION_4_MIRRORED: &str = "r2q1rk1/pP1p2pp/Q4n2/bbp1p3/Np6/1B3NBn/pPPP1PPP/R3K2R b";
+const POSITION_5: &str = "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w";
+const POSITION_6: &str = "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w";
+
+#[test]
+fn epd_rejects_illegal_positions() {
+    assert!(matches!(
+        EpdEntry::parse("8/8/8/8/8/8/8/8 w - - ;D1 0"),
+        Err(EpdError::Fen(FenError::IllegalPosition(_)))
+    ));
+}
+
+#[test]
+fn start_position() {
+    check(START, false);
+}
+
+#[test]
+fn kiwipete() {
+    check(KIWIPETE, false);
+}
+
+#[test]
+fn position_3() {
+    check(POSITION_3, false);
+}
+
+#[test]
+fn position_4() {
+    check(POSITION_4, false);
+}
+
+#[test]
+fn position_4_mirrored() {
+    check(POSITION_4_MIRRORED, false);
+}
+
+#[test]
+fn position_5() {
+    check(POSITION_5, false);
+}
+
+#[test]
+fn position_6() {
+    check(POSITION_6, false);
+}
+
+#[test]
+#[ignore = "slow, run with --release -- --ignored"]
+fn deep_suite() {
+    for fen in [
+        START,
+        KIWIPETE,
+        POSITION_3,
+        POSITION_4,
+        POSITION_4_MIRRORED,
+        POSITION_5,
+        POSITION_6,
+    ] {
+        check(fen, true);
+    }
+}
+
+#[test]
+fn parallel_and_hashed_match_plain() {
+    let cache = PerftCache::new(1);
+    for (fen, depth) in [(KIWIPETE, 3), (POSITION_3, 4), (POSITION_4, 3)] {
+        let mut board = Board::from_fen(&entry(fen).fen);
+        let expected = perft(&mut board, depth);
+        assert_eq!(perft_parallel(&board, depth, 4, None), expected, "{}", fen);
+        // Twice, so the second run is answered from the cache.
+        for _ in 0..2 {
+            assert_eq!(
+                perft_parallel(&board, depth, 4, Some(&cache)),
+                expected,
+                "{}",
+                fen
+            );
+        }
+    }
+}
+
+#[test]
+fn leaf_statistics() {
+    // Rows of the reference tables on the chess programming wiki.
+    for (fen, depth, expected) in [
+        (
+            START,
+            4,
+            PerftStats {
+                nodes: 197281,
+                captures: 1576,
+                en_passants: 0,
+                castles: 0,
+                promotions: 0,
+                checks: 469,
+                discovered_checks: 0,
+                double_checks: 0,
+                checkmates: 8,
+            },
+        ),
+        (
+            KIWIPETE,
+            3,
+            PerftStats {
+                nodes: 97862,
+                captures: 17102,
+                en_passants: 45,
+                castles: 3162,
+                promotions: 0,
+                checks: 993,
+                discovered_checks: 0,
+                double_checks: 0,
+                checkmates: 1,
+            },
+        ),
+        (
+            POSITION_3,
+            4,
+            PerftStats {
+                nodes: 43238,
+                captures: 3348,
+                en_passants: 123,
+                castles: 0,
+                promotions: 0,
+                checks: 1680,
+                discovered_checks: 106,
+                double_checks: 0,
+                checkmates: 17,
+            },
+        ),
+        (
+            POSITION_4,
+            3,
+            PerftStats {
+                nodes: 9467,
+                captures: 1021,
+                en_passants: 4,
+                castles: 0,
+                promotions: 120,
+                checks: 38,
+                discovered_checks: 2,
+                double_checks: 0,
+                checkmates: 22,
+            },
+        ),
+    ] {
+        let mut board = Board::from_fen(&entry(fen).fen);
+        assert_eq!(perft_stats(&mut board, depth), expected, "{}", fen);
+    }
+}
+
+/// Captures and quiets split the full list with nothing left over or counted
+/// twice, and evasions are the full list in check and empty otherwise.
+#[test]
+fn staged_generation_matches_full_list() {
+    fn keys(board: &Board, mode: GenMode) -> Vec<u16> {
+        let mut keys: Vec<_> = generate_moves_mode(board, mode)
+            .iter()
+            .map(|m| m.pack())
+            .collect();
+        keys.sort_unstable();
+        keys
+    }
+
+    fn walk(board: &mut Board, depth: u32, checks: &mut u32) {
+        let all = keys(board, GenMode::All);
+        let captures = generate_moves_mode(board, GenMode::Captures);
+        let quiets = generate_moves_mode(board, GenMode::Quiets);
+        assert!(captures
+            .iter()
+            .all(|m| m.capture || m.promotion().is_some()));
+        assert!(quiets.iter().all(|m| !m.capture && m.promotion().is_none()));
+
+        let mut staged = keys(board, GenMode::Captures);
+        staged.extend(keys(board, GenMode::Quiets));
+        staged.sort_unstable();
+        assert_eq!(staged, all, "{}", board.to_fen());
+
+        let in_check = MoveMasks::new(board).checkers != 0;
+        *checks += in_check as u32;
+        let evasions = keys(board, GenMode::Evasions);
+        assert_eq!(evasions, if in_check { all } else { vec![] });
+
+        if depth == 0 {
+            return;
+        }
+        for &m in generate_moves(board).iter() {
+            let undo = board.make_move_unchecked(m);
+            walk(board, depth - 1, checks);
+            board.unmake_move(m, undo);
+        }
+    }
+
+    let mut checks = 0;
+    for entry in EpdEntry::parse_suite(SUITE).unwrap() {
+        walk(&mut Board::from_fen(&entry.fen), 2, &mut checks);
+    }
+    assert!(checks > 0);
+}
+
+#[test]
+fn packed_moves_round_trip() {
+    fn walk(board: &mut Board, depth: u32, seen: &mut [u32; 3]) {
+        for &m in generate_moves(board).iter() {
+            assert_eq!(board.unpack_move(PackedMove::from(m).0), Some(m), "{}", m);
+            match m.special {
+                Some(SpecialMove::Promotion(_)) => seen[0] += 1,
+                Some(SpecialMove::Castle) => seen[1] += 1,
+                Some(SpecialMove::EnPassant) => seen[2] += 1,
+                _ => (),
+            }
+            if depth > 1 {
+                let undo = board.make_move_unchecked(m);
+                walk(board, depth - 1, seen);
+                board.unmake_move(m, undo);
+            }
+        }
+    }
+
+    // Promotions, castles and en passant captures.
+    let mut seen = [0; 3];
+    for entry in EpdEntry::parse_suite(SUITE).unwrap() {
+        walk(&mut Board::from_fen(&entry.fen), 3, &mut seen);
+    }
+    assert!(seen.iter().all(|&n| n > 0), "{:?}", seen);
+}
+
+/// Plays every generated move through the checked `make_move` and makes sure
+/// the incremental state matches a full recompute after each one.
+#[test]
+fn make_move_keeps_board_consistent() {
+    fn walk(board: &mut Board, depth: u32) {
+        if depth == 0 {
+            return;
+        }
+        for &m in generate_moves(board).iter() {
+            let before = format!("{:?}", board);
+            let undo = board
+                .make_move(m)
+                .unwrap_or_else(|e| panic!("{}: {}", m, e));
+            assert_eq!(board.hash(), board.compute_hash(), "{}", m);
+            assert!(board.mailbox_matches_bitboards(), "{}", m);
+            walk(board, depth - 1);
+            board.unmake_move(m, undo);
+            assert_eq!(before, format!("{:?}", board), "{}", m);
+        }
+    }
+
+    for fen in [KIWIPETE, POSITION_3, POSITION_4, POSITION_5] {
+        walk(&mut Board::from_fen(&entry(fen).fen), 3);
+    }
+}
@@ -0,0 +1,107 @@
+use lib::{
+    board::{Board, DEFAULT_FEN},
+    fen::FenError,
+    piece::Color,
+    square::Square,
+    validate::PositionError,
+};
+
+fn errors(fen: &str) -> Vec<PositionError> {
+    Board::try_from_fen(fen).unwrap().validate()
+}
+
+#[test]
+fn legal_positions() {
+    for fen in [
+        DEFAULT_FEN,
+        "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1",
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+    ] {
+        assert_eq!(errors(fen), [], "{}", fen);
+        assert!(Board::try_from_fen_strict(fen).is_ok(), "{}", fen);
+    }
+}
+
+#[test]
+fn king_count() {
+    assert_eq!(
+        errors("8/8/8/8/8/8/8/8 w - - 0 1"),
+        [
+            PositionError::KingCount {
+                color: Color::White,
+                count: 0,
+            },
+            PositionError::KingCount {
+                color: Color::Black,
+                count: 0,
+            },
+        ]
+    );
+    assert_eq!(
+        errors("4k3/8/8/8/8/8/8/3KK3 w - - 0 1"),
+        [PositionError::KingCount {
+            color: Color::White,
+            count: 2,
+        }]
+    );
+}
+
+#[test]
+fn pawns_on_back_rank() {
+    assert_eq!(
+        errors("P3k3/8/8/8/8/8/8/4K2p w - - 0 1"),
+        [
+            PositionError::PawnOnBackRank(Square::H1),
+            PositionError::PawnOnBackRank(Square::A8),
+        ]
+    );
+}
+
+#[test]
+fn side_not_to_move_in_check() {
+    assert_eq!(
+        errors("4k3/8/8/8/8/8/8/4R1K1 w - - 0 1"),
+        [PositionError::OpponentInCheck]
+    );
+    assert_eq!(errors("4k3/8/8/8/8/8/8/4R1K1 b - - 0 1"), []);
+}
+
+#[test]
+fn impossible_castling() {
+    assert_eq!(
+        errors("4k3/8/8/8/8/8/8/4K3 w K - 0 1"),
+        [PositionError::CastlingWithoutRook('K')]
+    );
+    assert_eq!(
+        errors("r2k4/8/8/8/8/8/8/3K3R w Kq - 0 1"),
+        [
+            PositionError::CastlingWithoutKing('K'),
+            PositionError::CastlingWithoutKing('q'),
+        ]
+    );
+}
+
+#[test]
+fn impossible_en_passant() {
+    assert_eq!(
+        errors("4k3/8/8/8/4P3/8/8/4K3 w - e3 0 1"),
+        [PositionError::EnPassantWrongRank(Square::E3)]
+    );
+    assert_eq!(
+        errors("4k3/8/8/8/8/8/8/4K3 b - e3 0 1"),
+        [PositionError::EnPassantWithoutPawn(Square::E3)]
+    );
+    assert_eq!(
+        errors("4k3/8/8/8/4P3/4N3/8/4K3 b - e3 0 1"),
+        [PositionError::EnPassantOccupied(Square::E3)]
+    );
+}
+
+#[test]
+fn strict_parse_rejects_illegal_positions() {
+    assert_eq!(
+        Board::try_from_fen_strict("4k3/8/8/8/8/8/8/4R1K1 w - - 0 1").unwrap_err(),
+        FenError::IllegalPosition(vec![PositionError::OpponentInCheck])
+    );
+}